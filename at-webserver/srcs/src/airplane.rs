@@ -1,34 +1,50 @@
-use chrono::{Timelike, Utc};
+use chrono::{Datelike, Duration as ChronoDuration, NaiveTime, TimeZone, Utc};
 use chrono_tz::Asia::Shanghai;
-use std::error::Error;
+use chrono_tz::Tz;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, sleep_until, Instant};
 
+use crate::at::events::ModemEvent;
+use crate::at::scheduler::{CommandScheduler, Priority};
 use crate::at::ATClient;
-use crate::config::Config;
+use crate::config::{AirplaneTrigger, Config, ModuleTiming};
 
 /// 自动开关飞行模式功能
 pub struct AutoAirPlaneMode {
-    client: Arc<ATClient>,
+    scheduler: Arc<CommandScheduler>,
+    event_rx: broadcast::Receiver<ModemEvent>,
     enabled: bool,
-    action_time: String,
+    triggers: Vec<AirplaneTrigger>,
+    signal_loss_minutes: u32,
+    timing: ModuleTiming,
 }
 
 impl AutoAirPlaneMode {
-    pub fn new(client: Arc<ATClient>, config: Arc<Config>) -> Self {
+    pub fn new(scheduler: Arc<CommandScheduler>, client: Arc<ATClient>, config: Arc<Config>) -> Self {
         let auto_airplane = &config.auto_airplane;
+        let timing = config.module_timing;
+        let triggers = auto_airplane.action_time.triggers();
 
         let mode = Self {
-            client,
+            scheduler,
+            event_rx: client.event_tx.subscribe(),
             enabled: auto_airplane.enabled,
-            action_time: auto_airplane.action_time.clone(),
+            triggers,
+            signal_loss_minutes: auto_airplane.signal_loss_minutes,
+            timing,
         };
 
         if mode.enabled {
             println!("{}", "=".repeat(60));
             println!("自动开关飞行模式功能已启用");
-            println!("  操作时间: {}", mode.action_time);
+            for trigger in &mode.triggers {
+                println!("  触发时刻: {}{}", trigger.time, describe_weekdays(trigger.weekdays));
+            }
+            if mode.signal_loss_minutes > 0 {
+                println!("  信号丢失触发: 连续 {} 分钟无信号", mode.signal_loss_minutes);
+            }
             println!("{}", "=".repeat(60));
         }
 
@@ -45,31 +61,9 @@ impl AutoAirPlaneMode {
         }
     }
 
-    fn parse_action_time(&self) -> Result<(u32, u32), Box<dyn Error + Send + Sync>> {
-        let parts: Vec<&str> = self.action_time.split(':').collect();
-        if parts.len() != 2 {
-            return Err("无效的时间格式，需为 HH:MM".into());
-        }
-
-        let hour: u32 = parts[0].parse().map_err(|_| "无效的小时值")?;
-        let minute: u32 = parts[1].parse().map_err(|_| "无效的分钟值")?;
-
-        if hour >= 24 || minute >= 60 {
-            return Err("小时必须在0-23之间，分钟必须在0-59之间".into());
-        }
-
-        Ok((hour, minute))
-    }
-
-    fn is_action_time(&self, now: &chrono::DateTime<chrono_tz::Tz>) -> bool {
-        if let Ok((action_hour, action_minute)) = self.parse_action_time() {
-            return now.hour() == action_hour && now.minute() == action_minute;
-        }
-        false
-    }
-
     fn restart_airplane_mode(&self) {
-        let client = self.client.clone();
+        let scheduler = self.scheduler.clone();
+        let timing = self.timing;
         tokio::spawn(async move {
             println!(
                 "[{}] 自动重启飞行模式开始...",
@@ -77,20 +71,23 @@ impl AutoAirPlaneMode {
             );
 
             // 关闭飞行模式 (CFUN=0 开启飞行模式)
-            match client.send_command("AT+CFUN=0".into()).await {
+            match scheduler.submit_str(Priority::Scheduler, "AT+CFUN=0".into()).await {
                 Ok(_) => println!("飞行模式已开启"),
                 Err(e) => println!("开启飞行模式失败: {}", e),
             }
 
-            // 等待10秒
-            sleep(Duration::from_secs(5)).await;
+            // 给模块留出进入飞行模式的结算时间，不同模块/固件差异很大。
+            sleep(Duration::from_secs(timing.cfun_off_settle_secs)).await;
 
             // 打开飞行模式 (CFUN=1 关闭飞行模式)
-            match client.send_command("AT+CFUN=1".into()).await {
+            match scheduler.submit_str(Priority::Scheduler, "AT+CFUN=1".into()).await {
                 Ok(_) => println!("飞行模式已关闭"),
                 Err(e) => println!("关闭飞行模式失败: {}", e),
             }
 
+            // 退出飞行模式后重新驻网通常比进入慢得多，同样按配置的结算时间等待。
+            sleep(Duration::from_secs(timing.cfun_on_settle_secs)).await;
+
             println!(
                 "[{}] 自动重启飞行模式完成",
                 chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
@@ -98,23 +95,130 @@ impl AutoAirPlaneMode {
         });
     }
 
-    /// 启动监控循环
-    pub async fn monitor_loop(self) {
+    /// 触发器列表里最近的下一次到期时刻；没有任何触发器能匹配（比如某个
+    /// 触发器的星期掩码为 0）时跳过它，全部跳过则返回 `None`。
+    fn next_due(&self, now: chrono::DateTime<Tz>) -> Option<chrono::DateTime<Tz>> {
+        self.triggers
+            .iter()
+            .filter_map(|trigger| next_occurrence(trigger, now))
+            .min()
+    }
+
+    /// 启动监控循环：按时钟触发器精确睡到下一次到期，期间并行盯着
+    /// `event_tx` 上的信号强度事件，信号连续 0 格超过
+    /// `signal_loss_minutes` 分钟也会触发一次重启。
+    pub async fn monitor_loop(mut self) {
         tokio::spawn(async move {
-            loop {
-                if self.enabled {
-                    let now = Utc::now().with_timezone(&Shanghai);
-                    println!("当前时间: {}", now.format("%H:%M"));
+            if !self.enabled {
+                return;
+            }
+
+            // 只有真正收到过 0 格信号上报才开始计时；modem 压根不上报信号
+            // （没启用 `^HCSQ`/`+CSQ` 主动上报）时这里会一直是 `None`，不会
+            // 把“没收到事件”误判成“信号一直是 0”。
+            let mut zero_signal_since: Option<Instant> = None;
 
-                    if self.is_action_time(&now) {
+            loop {
+                let now = Utc::now().with_timezone(&Shanghai);
+                // 没有任何触发器能匹配时退化成每小时醒一次，只为了继续跑
+                // 信号丢失检查，而不是永久挂起。
+                let sleep_duration = self
+                    .next_due(now)
+                    .and_then(|due| (due - now).to_std().ok())
+                    .unwrap_or(Duration::from_secs(3600));
+
+                tokio::select! {
+                    _ = sleep_until(Instant::now() + sleep_duration) => {
+                        println!(
+                            "[AutoAirPlaneMode] 到达触发时刻 {}，开始重启飞行模式",
+                            Utc::now().with_timezone(&Shanghai).format("%Y-%m-%d %H:%M:%S")
+                        );
                         self.restart_airplane_mode();
-                        // 等待60秒，避免在同一分钟内重复触发
+                        // 避免同一分钟内因为时钟抖动重复触发。
                         sleep(Duration::from_secs(60)).await;
                     }
+                    event = self.event_rx.recv() => {
+                        match event {
+                            Ok(ModemEvent::Signal { rssi }) if rssi > 0 => {
+                                zero_signal_since = None;
+                            }
+                            Ok(ModemEvent::Signal { rssi }) if rssi <= 0 => {
+                                zero_signal_since.get_or_insert_with(Instant::now);
+                            }
+                            Ok(_) => {}
+                            Err(broadcast::error::RecvError::Lagged(_)) => {}
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
+                }
+
+                if self.signal_loss_minutes > 0 {
+                    if let Some(since) = zero_signal_since {
+                        if since.elapsed() >= Duration::from_secs(self.signal_loss_minutes as u64 * 60) {
+                            println!(
+                                "[AutoAirPlaneMode] 信号已连续 {} 分钟无信号，触发重启飞行模式",
+                                self.signal_loss_minutes
+                            );
+                            self.restart_airplane_mode();
+                            zero_signal_since = None;
+                        }
+                    }
                 }
-                // 每分钟查询一次
-                sleep(Duration::from_secs(60)).await;
             }
         });
     }
-}
\ No newline at end of file
+}
+
+fn describe_weekdays(weekdays: Option<u8>) -> String {
+    match weekdays {
+        None => String::new(),
+        Some(mask) => {
+            const NAMES: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+            let days: Vec<&str> = (0..7)
+                .filter(|bit| mask & (1 << bit) != 0)
+                .map(|bit| NAMES[bit as usize])
+                .collect();
+            if days.is_empty() {
+                " (未选择任何星期，不会触发)".to_string()
+            } else {
+                format!(" (仅周{})", days.join("、"))
+            }
+        }
+    }
+}
+
+/// 某个触发器从 `now` 起最近的下一次到期时刻；最多往后找 8 天，找不到
+/// （星期掩码是 0）返回 `None`。
+fn next_occurrence(trigger: &AirplaneTrigger, now: chrono::DateTime<Tz>) -> Option<chrono::DateTime<Tz>> {
+    let (hour, minute) = parse_time(&trigger.time)?;
+    let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+
+    for day_offset in 0..8 {
+        let date = now.date_naive() + ChronoDuration::days(day_offset);
+        let candidate = Shanghai.from_local_datetime(&date.and_time(time)).single()?;
+        if candidate <= now {
+            continue;
+        }
+        if let Some(mask) = trigger.weekdays {
+            let bit = candidate.weekday().num_days_from_monday();
+            if mask & (1 << bit) == 0 {
+                continue;
+            }
+        }
+        return Some(candidate);
+    }
+    None
+}
+
+fn parse_time(value: &str) -> Option<(u32, u32)> {
+    let parts: Vec<&str> = value.split(':').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+    let hour: u32 = parts[0].parse().ok()?;
+    let minute: u32 = parts[1].parse().ok()?;
+    if hour >= 24 || minute >= 60 {
+        return None;
+    }
+    Some((hour, minute))
+}
@@ -1,115 +1,376 @@
-// use async_trait::async_trait;
 use std::error::Error;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{Mutex, broadcast};
-use tokio::time::{sleep, timeout};
+use tokio::sync::{Mutex, broadcast, mpsc, watch};
+use tokio::time::{Instant, sleep, timeout};
 
-use crate::config::Config;
-use crate::at::connection::{ATConnection, NetworkATConn, SerialATConn, TomModemATConn};
-//  connection::ATConnection;
+use crate::at::connection::{ATConnection, FailoverATConn, build_connection};
+use crate::at::events::{classify_event, ModemEvent};
+use crate::at::parser::{AtResponse, FinalStatus, Line, LineDigester, classify_line};
+use crate::config::{AtConfig, AtEndpoint, ModuleTiming};
+
+/// 当前在途命令的回传通道：回显文本用于 `classify_line` 识别自己的回显，
+/// `tx` 把除 URC 外的每一行转发给正在等待的 `send_command_typed`。
+struct PendingCommand {
+    echo_cmd: String,
+    tx: mpsc::UnboundedSender<Line>,
+}
+
+/// 模块的开机驻网状态，供 Web UI / MQTT 状态面读取，不必各自重新探测。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleState {
+    /// 刚建链，还没跑完开机自检流程。
+    Booting,
+    NoSim,
+    SimLocked,
+    NoSignal,
+    Searching,
+    Registered,
+}
 
 pub struct ATClient {
     pub conn: Arc<Mutex<Box<dyn ATConnection>>>,
     pub urc_tx: broadcast::Sender<String>,
-    // pub config: Arc<Config>,
+    /// 从 `urc_tx` 上的原始行里分类出来的结构化事件（短信/来电/存储已
+    /// 满/信号），供 WebSocket 类型化通知和 `NotifierHub` 复用；分类不出
+    /// 来的 URC 只在 `urc_tx` 上出现，不会喂到这里。
+    pub event_tx: broadcast::Sender<ModemEvent>,
+    pub timing: ModuleTiming,
+    pending: Mutex<Option<PendingCommand>>,
+    state_tx: watch::Sender<ModuleState>,
 }
 
 impl ATClient {
-    pub fn new(config: &Arc<Config>) -> Result<Self, Box<dyn Error>> {
-        let at_config = &config.at_config;
-
-        let conn: Box<dyn ATConnection> = if at_config.conn_type == "NETWORK" {
-            Box::new(NetworkATConn::new(at_config.network.clone()))
-        } else {
-            if at_config.serial.method == "TOM_MODEM" {
-                Box::new(TomModemATConn::new(
-                    at_config.serial.port.clone(),
-                    at_config.serial.timeout,
-                    at_config.serial.feature.clone(),
-                ))
-            } else {
-                Box::new(SerialATConn::new(at_config.serial.clone()))
-            }
+    /// 为单个模块构建客户端。多模场景下 `at::registry::ModemRegistry` 会
+    /// 为配置里的每个模块各调用一次。`timing` 统一来自 `Config::module_timing`，
+    /// 决定命令超时、轮询间隔、CFUN 切换后的结算等待这些可调参数。
+    pub fn new(at_config: &AtConfig, timing: ModuleTiming) -> Result<Self, Box<dyn Error>> {
+        // 主连接目标排第一位，`FALLBACK_ENDPOINTS` 里声明的后备端点按顺序
+        // 跟在后面；只有一个端点时 `FailoverATConn` 的行为与直接用它完全
+        // 一致，不引入额外开销。
+        let primary = AtEndpoint {
+            conn_type: at_config.conn_type.clone(),
+            network: at_config.network.clone(),
+            serial: at_config.serial.clone(),
+            ws_relay: at_config.ws_relay.clone(),
         };
+        let endpoints = std::iter::once(&primary)
+            .chain(at_config.fallback_endpoints.iter())
+            .enumerate()
+            .map(|(i, endpoint)| {
+                let label = if i == 0 {
+                    "primary".to_string()
+                } else {
+                    format!("fallback-{}", i)
+                };
+                (label, build_connection(endpoint))
+            })
+            .collect();
+        let conn: Box<dyn ATConnection> = Box::new(FailoverATConn::new(endpoints));
 
         let (tx, _) = broadcast::channel(1024);
+        let (event_tx, _) = broadcast::channel(1024);
+        let (state_tx, _) = watch::channel(ModuleState::Booting);
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             urc_tx: tx,
-            // config,
+            event_tx,
+            timing,
+            pending: Mutex::new(None),
+            state_tx,
         })
     }
 
-    /// 发送 AT 命令并等待响应
-    pub async fn send_command(
+    /// 当前的开机驻网状态。
+    pub fn module_state(&self) -> ModuleState {
+        *self.state_tx.borrow()
+    }
+
+    /// 订阅状态变化，供 Web UI / MQTT 状态面跟踪。
+    pub fn subscribe_state(&self) -> watch::Receiver<ModuleState> {
+        self.state_tx.subscribe()
+    }
+
+    /// 启动专属的读取任务：唯一从 `conn` 读字节、切行、分类的地方。分类
+    /// 成 URC 的行直接广播到 `urc_tx`；其余的（回显/信息行/终止码）转发给
+    /// 当前在途命令的回传通道。这样 `send_command` 之间不再抢着 poll
+    /// `conn.receive()`，两条命令之间到达的 URC 也不会被当成残留丢弃。
+    pub fn spawn_reader(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut digester = LineDigester::new();
+            // `+CMT:` 头部后紧跟一行不带任何前缀的 PDU/正文，本身不会被
+            // `classify_line` 认作 URC；借这个标记把下一行也并入同一条 URC。
+            let mut expect_cmt_payload = false;
+            loop {
+                let data = {
+                    let mut conn = self.conn.lock().await;
+                    if !conn.is_connected() {
+                        None
+                    } else {
+                        conn.receive().await.ok()
+                    }
+                };
+
+                match data {
+                    Some(bytes) if !bytes.is_empty() => {
+                        let chunk = String::from_utf8_lossy(&bytes).to_string();
+                        let echo_cmd = self
+                            .pending
+                            .lock()
+                            .await
+                            .as_ref()
+                            .map(|p| p.echo_cmd.clone())
+                            .unwrap_or_default();
+
+                        for line in digester.feed(&chunk) {
+                            if expect_cmt_payload {
+                                expect_cmt_payload = false;
+                                println!("[URC DETECTED] <== {:?} (+CMT 续行)", line);
+                                let _ = self.urc_tx.send(line);
+                                continue;
+                            }
+
+                            let classified = classify_line(&line, &echo_cmd);
+                            if let Line::Urc(ref urc) = classified {
+                                println!("[URC DETECTED] <== {:?}", urc);
+                                expect_cmt_payload = urc.starts_with("+CMT:");
+                                if let Some(event) = classify_event(urc) {
+                                    let _ = self.event_tx.send(event);
+                                }
+                                let _ = self.urc_tx.send(urc.clone());
+                                continue;
+                            }
+                            if let Some(pending) = self.pending.lock().await.as_ref() {
+                                let _ = pending.tx.send(classified);
+                            }
+                        }
+                    }
+                    _ => sleep(Duration::from_millis(10)).await,
+                }
+            }
+        });
+    }
+
+    /// 发送 AT 命令并等待带类型终止码的响应。
+    ///
+    /// 实际的读取由 `spawn_reader` 起的后台任务完成；这里只是登记一个回传
+    /// 通道、发送命令字节，然后在通道上等到终止码或超时为止。
+    pub async fn send_command_typed(
+        &self,
+        command: String,
+    ) -> Result<AtResponse, Box<dyn Error + Send + Sync>> {
+        self.send_command_inner(command, None).await
+    }
+
+    /// 发送一条需要 `>` 提示符续传正文的命令（目前是 `AT+CMGS=<len>` 这类
+    /// PDU 模式短信发送）：先发命令本身，看到 `>` 提示符后把 `pdu_hex` 接
+    /// Ctrl-Z（`0x1A`）写回去，再像普通命令一样等终止码。
+    pub async fn send_sms_pdu(
+        &self,
+        command: String,
+        pdu_hex: String,
+    ) -> Result<AtResponse, Box<dyn Error + Send + Sync>> {
+        self.send_command_inner(command, Some(pdu_hex)).await
+    }
+
+    async fn send_command_inner(
         &self,
         mut command: String,
-    ) -> Result<String, Box<dyn Error + Send + Sync>> {
-        let mut conn = self.conn.lock().await;
-        let original_cmd = command.trim().to_string();
+        mut pdu_body: Option<String>,
+    ) -> Result<AtResponse, Box<dyn Error + Send + Sync>> {
+        let echo_cmd = command.trim().to_string();
         if !command.ends_with("\r\n") {
             command = command.trim_end().to_string();
             command.push_str("\r\n");
         }
 
-        // 1. 清理旧残留，防止 ping 干扰指令结果
-        while let Ok(d) = timeout(Duration::from_millis(10), conn.receive())
-            .await
-            .unwrap_or(Ok(vec![]))
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        *self.pending.lock().await = Some(PendingCommand {
+            echo_cmd,
+            tx,
+        });
+
+        println!("[DEBUG] ==> TX: {:?}", command);
         {
-            if d.is_empty() {
-                break;
-            }
+            let mut conn = self.conn.lock().await;
+            conn.send(command.as_bytes()).await?;
         }
 
-        println!("[DEBUG] ==> TX: {:?}", command);
-        conn.send(command.as_bytes()).await?;
-
-        let mut raw_response = String::new();
-        let start = std::time::Instant::now();
-
-        // 2. 超时设为 1000ms
-        while start.elapsed() < Duration::from_millis(1000) {
-            if let Ok(data) = conn.receive().await {
-                if !data.is_empty() {
-                    raw_response.push_str(&String::from_utf8_lossy(&data));
-                    // 如果看到 OK 或 ERROR，说明指令响应结束
-                    if raw_response.contains("OK\r\n") || raw_response.contains("ERROR") {
-                        break;
+        let mut info_lines = Vec::new();
+        let start = Instant::now();
+        let command_timeout = Duration::from_millis(self.timing.command_timeout_ms);
+        let poll_interval = Duration::from_millis(self.timing.poll_interval_ms);
+
+        let result = loop {
+            if start.elapsed() >= command_timeout {
+                break AtResponse {
+                    lines: info_lines,
+                    status: FinalStatus::Timeout,
+                };
+            }
+
+            match timeout(poll_interval, rx.recv()).await {
+                Ok(Some(Line::Echo(_))) | Ok(Some(Line::Urc(_))) => {}
+                Ok(Some(Line::Prompt)) => {
+                    if let Some(body) = pdu_body.take() {
+                        println!("[DEBUG] ==> TX (PDU): {:?}", body);
+                        let mut payload = body.into_bytes();
+                        payload.push(0x1A); // Ctrl-Z 结束 PDU 正文
+                        let mut conn = self.conn.lock().await;
+                        let _ = conn.send(&payload).await;
                     }
                 }
+                Ok(Some(Line::Data(data))) => info_lines.push(data),
+                Ok(Some(Line::Final(status))) => {
+                    break AtResponse {
+                        lines: info_lines,
+                        status,
+                    };
+                }
+                Ok(None) => {
+                    break AtResponse {
+                        lines: info_lines,
+                        status: FinalStatus::Timeout,
+                    };
+                }
+                Err(_) => {} // 本轮没有新行，继续等待直到超过总超时
             }
-            sleep(Duration::from_millis(10)).await;
+        };
+
+        *self.pending.lock().await = None;
+        println!("[DEBUG] <== RX: {:?}", result.body());
+        Ok(result)
+    }
+
+    /// 向后兼容的字符串接口：成功时返回响应正文，终止码非 `OK` 时返回错误。
+    pub async fn send_command(
+        &self,
+        command: String,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let response = self.send_command_typed(command).await?;
+        if response.is_ok() {
+            Ok(response.body())
+        } else {
+            Err(match response.status {
+                FinalStatus::Error => "ERROR".into(),
+                other => other.to_string().into(),
+            })
         }
+    }
 
-        let mut cleaned = raw_response.replace("ping", "").trim().to_string();
-        if cleaned.trim_start().starts_with(&original_cmd) {
-            if let Some(pos) = cleaned.find('\n') {
-                cleaned = cleaned[(pos + 1)..].to_string();
+    /// 对某条命令按 `interval` 重试，直到 `predicate` 对响应返回 `true` 或
+    /// `budget` 耗尽为止；到期仍不满足时打印 `label` 并返回 `None`。
+    async fn retry_until(
+        &self,
+        command: &str,
+        predicate: impl Fn(&AtResponse) -> bool,
+        budget: Duration,
+        interval: Duration,
+        label: &str,
+    ) -> Option<AtResponse> {
+        let deadline = Instant::now() + budget;
+        loop {
+            if let Ok(response) = self.send_command_typed(command.to_string()).await {
+                if predicate(&response) {
+                    return Some(response);
+                }
             }
+            if Instant::now() >= deadline {
+                println!("[ATClient] {} 超时", label);
+                return None;
+            }
+            sleep(interval).await;
         }
+    }
+
+    /// 分阶段完成模块开机自检，每一步都退避重试到出现期望响应或本阶段超时
+    /// 为止，而不是像过去那样对 `ATE0`/`CNMI`/`CMGF`/`CLIP` 这几条命令直接
+    /// `let _ =` 忽略结果、让一个还在开机或没插 SIM 的模块悄悄带着半吊子
+    /// 状态启动。各阶段的进度写进 `state_tx`，供 Web UI / MQTT 状态面读取。
+    pub async fn bring_up(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let _ = self.state_tx.send(ModuleState::Booting);
+
+        let fast_retry = Duration::from_millis(500);
 
-        let result = cleaned.trim().to_string();
-        println!("[DEBUG] <== RX: {:?}", result);
+        // 1. AT 握手：模块可能还在开机自检，重试而不是假设第一次就通。
+        self.retry_until(
+            "AT\r\n",
+            |r| r.is_ok(),
+            Duration::from_secs(10),
+            fast_retry,
+            "等待模块响应 AT",
+        )
+        .await
+        .ok_or("模块在超时内未响应 AT")?;
 
-        // 如果结果包含 ERROR，返回 Err 分支
-        if result.contains("ERROR") {
-            return Err("ERROR".into());
+        // 2. SIM 卡状态：非 READY 的情况直接当成致命错误上报，不再继续。
+        let cpin = self
+            .retry_until(
+                "AT+CPIN?\r\n",
+                |r| r.is_ok(),
+                Duration::from_secs(10),
+                fast_retry,
+                "查询 SIM 卡状态",
+            )
+            .await
+            .ok_or("查询 SIM 卡状态超时")?;
+
+        let cpin_body = cpin.body();
+        if cpin_body.contains("SIM PIN") || cpin_body.contains("SIM PUK") {
+            let _ = self.state_tx.send(ModuleState::SimLocked);
+            return Err(format!("SIM 卡被锁定，需要解锁: {}", cpin_body).into());
+        }
+        if !cpin_body.contains("READY") {
+            let _ = self.state_tx.send(ModuleState::NoSim);
+            return Err(format!("未检测到可用 SIM 卡: {}", cpin_body).into());
         }
 
-        if result.is_empty() && start.elapsed() >= Duration::from_millis(1000) {
-            return Err("TIMEOUT".into());
+        // 3. 信号强度：RSSI=99 代表暂测不到信号，只警告不阻断，交给第 4 步
+        // 的驻网轮询自行判断是不是真的搜不到网。
+        match self.send_command("AT+CSQ\r\n".to_string()).await {
+            Ok(body)
+                if body
+                    .split(':')
+                    .nth(1)
+                    .and_then(|s| s.split(',').next())
+                    .map(|s| s.trim())
+                    == Some("99") =>
+            {
+                let _ = self.state_tx.send(ModuleState::NoSignal);
+                println!("[ATClient] 警告: AT+CSQ 返回 RSSI=99，暂未测得信号");
+            }
+            Err(e) => println!("[ATClient] 警告: AT+CSQ 查询失败: {}", e),
+            _ => {}
         }
 
-        Ok(result)
-    }
+        // 4. 驻网：轮询 AT+CEREG? 直到 "0,1"/"0,5"，超时只警告、仍继续往下
+        // 走，避免模块长时间搜网时把整个初始化流程卡死。
+        let _ = self.state_tx.send(ModuleState::Searching);
+        let registered = self
+            .retry_until(
+                "AT+CEREG?\r\n",
+                |r| r.is_ok() && (r.body().contains("0,1") || r.body().contains("0,5")),
+                Duration::from_secs(30),
+                Duration::from_secs(self.timing.registration_poll_interval_secs.max(1)),
+                "等待网络注册",
+            )
+            .await
+            .is_some();
+
+        if registered {
+            let _ = self.state_tx.send(ModuleState::Registered);
+        } else {
+            println!("[ATClient] 警告: 等待网络注册超时，继续执行初始化");
+        }
 
-    /// 初始化模块（ATE0, CNMI, CMGF, CLIP）
-    pub async fn init_module(&self) {
+        // 5. 只有跑到这里才下发这些一次性初始化命令。
         let _ = self.send_command("ATE0".into()).await;
+        // 数字形式上报 +CME ERROR，否则模块默认直接返回裸 ERROR，解析不出错误码。
+        let _ = self.send_command("AT+CMEE=1".into()).await;
         let _ = self.send_command("AT+CNMI=2,1,0,2,0".into()).await;
         let _ = self.send_command("AT+CMGF=0".into()).await;
         let _ = self.send_command("AT+CLIP=1".into()).await;
+
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,20 +1,91 @@
 use async_trait::async_trait;
 use std::error::Error;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::time::timeout;
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout, Instant};
+use tokio_rustls::client::TlsStream;
 use tokio_serial::{SerialPortBuilderExt, SerialStream};
-use crate::config::{NetworkConfig, SerialConfig};
+use crate::at::parser::{classify_line, AtResponse, FinalStatus, FramedResponse, Line, LineDigester};
+use crate::at::supervisor::LinkState;
+use crate::config::{AtEndpoint, NetworkConfig, SerialConfig, WebSocketConfig};
+use crate::tls;
 
 // ========== AT 连接抽象 ==========
 
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_millis(25);
+
 #[async_trait]
 pub trait ATConnection: Send {
     async fn connect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>;
     async fn send(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>>;
+    /// 从连接读一次原始字节，超时/无数据都可能返回空结果，不做任何分行
+    /// /分类；`receive_response` 在它之上把这些字节攒成完整的一条命令
+    /// 响应。
     async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
     fn is_connected(&self) -> bool;
+    /// 断开底层链路，使下一次 `connect()` 重新建立连接。供健康检查/监督
+    /// 任务在探测失败时主动拆除连接使用。
+    fn disconnect(&mut self);
+    /// 设置 `receive()` 的读取超时，默认 25ms。健康探测等场景需要更长的
+    /// 超时以避免与正常数据竞争。
+    fn set_read_timeout(&mut self, timeout: Duration);
+
+    /// 在 `receive()` 之上做一层帧定界：反复读取、按 `\r\n` 切行，直到看
+    /// 到终止码（`OK`/`ERROR`/`+CME ERROR:`/`+CMS ERROR:` 等）或者
+    /// `deadline` 耗尽为止，而不是像裸 `receive()` 那样把单次 25ms 读到的
+    /// 任意字节原样吐给调用方——长响应（`AT+COPS=?` 扫网、PDU 正文）一次
+    /// 读不完就会被截断。期间收到的 URC 单独收进
+    /// `FramedResponse::urcs`，不会混进命令响应正文。
+    ///
+    /// 这是 `ATConnection` 实现都能免费获得的默认实现；`ATClient` 自己的
+    /// `spawn_reader` 走的是另一条路径（常驻任务 + `LineDigester`），不
+    /// 经过这里，也是唯一允许常驻 `receive()` 这条连接的地方。这个方法是
+    /// 给没有常驻读取任务、只是偶尔直接拿着 `ATConnection` 用一次的调用
+    /// 方，比如 `ReconnectingATConn::reinit` 在刚 `connect()` 成功、读取
+    /// 任务还没重新接上之前重放初始化命令。
+    async fn receive_response(
+        &mut self,
+        echo_cmd: &str,
+        deadline: Duration,
+    ) -> Result<FramedResponse, Box<dyn Error + Send + Sync>> {
+        let mut digester = LineDigester::new();
+        let mut lines = Vec::new();
+        let mut urcs = Vec::new();
+        let start = Instant::now();
+
+        loop {
+            if start.elapsed() >= deadline {
+                return Ok(FramedResponse {
+                    response: AtResponse {
+                        lines,
+                        status: FinalStatus::Timeout,
+                    },
+                    urcs,
+                });
+            }
+
+            let chunk = match self.receive().await {
+                Ok(bytes) if !bytes.is_empty() => String::from_utf8_lossy(&bytes).to_string(),
+                _ => continue,
+            };
+
+            for line in digester.feed(&chunk) {
+                match classify_line(&line, echo_cmd) {
+                    Line::Echo(_) | Line::Prompt => {}
+                    Line::Urc(urc) => urcs.push(urc),
+                    Line::Data(data) => lines.push(data),
+                    Line::Final(status) => {
+                        return Ok(FramedResponse {
+                            response: AtResponse { lines, status },
+                            urcs,
+                        });
+                    }
+                }
+            }
+        }
+    }
 }
 
 // ========== 串口连接实现 ==========
@@ -22,6 +93,7 @@ pub trait ATConnection: Send {
 pub struct SerialATConn {
     pub config: SerialConfig,
     stream: Option<SerialStream>,
+    read_timeout: Duration,
 }
 
 impl SerialATConn {
@@ -29,6 +101,7 @@ impl SerialATConn {
         Self {
             config,
             stream: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
         }
     }
 }
@@ -53,7 +126,7 @@ impl ATConnection for SerialATConn {
     async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
         if let Some(s) = &mut self.stream {
             let mut buf = vec![0u8; 1024];
-            let n = timeout(Duration::from_millis(25), s.read(&mut buf)).await??;
+            let n = timeout(self.read_timeout, s.read(&mut buf)).await??;
             buf.truncate(n);
             return Ok(buf);
         }
@@ -63,6 +136,14 @@ impl ATConnection for SerialATConn {
     fn is_connected(&self) -> bool {
         self.stream.is_some()
     }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = timeout;
+    }
 }
 
 // ========== 网络 TCP 连接实现 ==========
@@ -70,6 +151,7 @@ impl ATConnection for SerialATConn {
 pub struct NetworkATConn {
     pub config: NetworkConfig,
     stream: Option<TcpStream>,
+    read_timeout: Duration,
 }
 
 impl NetworkATConn {
@@ -77,6 +159,7 @@ impl NetworkATConn {
         Self {
             config,
             stream: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
         }
     }
 }
@@ -104,7 +187,81 @@ impl ATConnection for NetworkATConn {
     async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
         if let Some(s) = &mut self.stream {
             let mut buf = vec![0u8; 1024];
-            let n = timeout(Duration::from_millis(25), s.read(&mut buf)).await??;
+            let n = timeout(self.read_timeout, s.read(&mut buf)).await??;
+            buf.truncate(n);
+            return Ok(buf);
+        }
+        Err("Disconnected".into())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = timeout;
+    }
+}
+
+// ========== TLS 网络连接实现 ==========
+
+/// 加了一层 TLS 的 `NetworkATConn`：建 TCP 之后、收发任何 AT 命令之前先
+/// 按 `config.tls` 做一次握手，校验用 `config.host` 作 SNI。握手完成后
+/// `send`/`receive` 的实现和明文版完全一样，因为 `TlsStream` 同样实现了
+/// `AsyncRead`/`AsyncWrite`，复用同一套基于超时的读循环即可。
+pub struct TlsNetworkATConn {
+    pub config: NetworkConfig,
+    stream: Option<TlsStream<TcpStream>>,
+    read_timeout: Duration,
+}
+
+impl TlsNetworkATConn {
+    pub fn new(config: NetworkConfig) -> Self {
+        Self {
+            config,
+            stream: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+        }
+    }
+}
+
+#[async_trait]
+impl ATConnection for TlsNetworkATConn {
+    async fn connect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let addr = format!("{}:{}", self.config.host, self.config.port);
+        let tcp = timeout(
+            Duration::from_secs(self.config.timeout),
+            TcpStream::connect(addr),
+        )
+        .await??;
+
+        let connector = tls::build_network_connector(&self.config.tls)?;
+        let server_name = rustls::pki_types::ServerName::try_from(self.config.host.clone())?;
+        let stream = timeout(
+            Duration::from_secs(self.config.timeout),
+            connector.connect(server_name, tcp),
+        )
+        .await??;
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        if let Some(s) = &mut self.stream {
+            return Ok(s.write(data).await?);
+        }
+        Err("Disconnected".into())
+    }
+
+    async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        if let Some(s) = &mut self.stream {
+            let mut buf = vec![0u8; 1024];
+            let n = timeout(self.read_timeout, s.read(&mut buf)).await??;
             buf.truncate(n);
             return Ok(buf);
         }
@@ -114,6 +271,14 @@ impl ATConnection for NetworkATConn {
     fn is_connected(&self) -> bool {
         self.stream.is_some()
     }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = timeout;
+    }
 }
 
 // ========== TomModem 外部命令实现 ==========
@@ -189,4 +354,397 @@ impl ATConnection for TomModemATConn {
     fn is_connected(&self) -> bool {
         self.is_connected
     }
-}
\ No newline at end of file
+
+    fn disconnect(&mut self) {
+        self.is_connected = false;
+        self.response = None;
+    }
+
+    fn set_read_timeout(&mut self, _timeout: Duration) {
+        // tom_modem 每次调用都是同步取一次应答，没有独立的读超时可调。
+    }
+}
+
+// ========== WebSocket 中继连接实现 ==========
+
+type WsRelayStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>;
+
+/// 经一个 WebSocket 中继端点收发 AT 命令，供 NAT 后面、没法直接转发原始
+/// TCP/串口的模组使用：连上 `config.url`（`ws://`/`wss://` 均可，TLS 由
+/// `tokio_tungstenite` 按 scheme 自行处理），把每条 AT 命令当一个二进制
+/// 帧发出去，再把收到的帧原样吐给上层的帧定界逻辑。`Ping`/`Pong` 由
+/// `tokio_tungstenite` 在协议层自动应答，这里只在配置了
+/// `ping_interval_secs` 时额外主动发一个 `Ping` 保活；对端的 `Close` 帧
+/// 按其它连接实现的约定映射成 `"Disconnected"`，重连/会话层无需特殊处理
+/// 这条传输。
+pub struct WebSocketATConn {
+    pub config: WebSocketConfig,
+    stream: Option<WsRelayStream>,
+    read_timeout: Duration,
+    last_activity: Instant,
+}
+
+impl WebSocketATConn {
+    pub fn new(config: WebSocketConfig) -> Self {
+        Self {
+            config,
+            stream: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            last_activity: Instant::now(),
+        }
+    }
+}
+
+#[async_trait]
+impl ATConnection for WebSocketATConn {
+    async fn connect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+        use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+        let mut request = self.config.url.as_str().into_client_request()?;
+        if !self.config.token.is_empty() {
+            request.headers_mut().insert(
+                "Authorization",
+                HeaderValue::from_str(&format!("Bearer {}", self.config.token))?,
+            );
+        }
+
+        let (stream, _response) = tokio_tungstenite::connect_async(request).await?;
+        self.stream = Some(stream);
+        self.last_activity = Instant::now();
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        use futures_util::SinkExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        if let Some(s) = &mut self.stream {
+            let len = data.len();
+            s.send(Message::Binary(data.to_vec())).await?;
+            return Ok(len);
+        }
+        Err("Disconnected".into())
+    }
+
+    async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        if self.stream.is_none() {
+            return Err("Disconnected".into());
+        }
+
+        if self.config.ping_interval_secs > 0
+            && self.last_activity.elapsed() >= Duration::from_secs(self.config.ping_interval_secs)
+        {
+            if let Some(s) = &mut self.stream {
+                let _ = s.send(Message::Ping(Vec::new())).await;
+            }
+            self.last_activity = Instant::now();
+        }
+
+        let s = self.stream.as_mut().expect("刚检查过 stream 不是 None");
+        match timeout(self.read_timeout, s.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => {
+                self.last_activity = Instant::now();
+                Ok(text.into_bytes())
+            }
+            Ok(Some(Ok(Message::Binary(bytes)))) => {
+                self.last_activity = Instant::now();
+                Ok(bytes)
+            }
+            Ok(Some(Ok(Message::Ping(_) | Message::Pong(_)))) => {
+                self.last_activity = Instant::now();
+                Ok(Vec::new())
+            }
+            Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                self.stream = None;
+                Err("Disconnected".into())
+            }
+            Ok(Some(Err(e))) => Err(e.into()),
+            // 读超时：底层没有新帧，不是故障，留给上层的轮询循环继续等。
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        self.read_timeout = timeout;
+    }
+}
+
+/// 按 `AtEndpoint` 的 `TYPE` 字段挑选具体实现，`ATClient::new` 和
+/// `FailoverATConn` 在构建主/备连接时共用这一份逻辑。
+pub fn build_connection(endpoint: &AtEndpoint) -> Box<dyn ATConnection> {
+    if endpoint.conn_type == "NETWORK" {
+        if endpoint.network.tls.enabled {
+            Box::new(TlsNetworkATConn::new(endpoint.network.clone()))
+        } else {
+            Box::new(NetworkATConn::new(endpoint.network.clone()))
+        }
+    } else if endpoint.conn_type == "WEBSOCKET" {
+        Box::new(WebSocketATConn::new(endpoint.ws_relay.clone()))
+    } else if endpoint.serial.method == "TOM_MODEM" {
+        Box::new(TomModemATConn::new(
+            endpoint.serial.port.clone(),
+            endpoint.serial.timeout,
+            endpoint.serial.feature.clone(),
+        ))
+    } else {
+        Box::new(SerialATConn::new(endpoint.serial.clone()))
+    }
+}
+
+// ========== 多端点故障转移封装 ==========
+
+/// 连续失败多少次才认定当前端点不健康、切到下一个。
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// 按优先级持有若干具体连接实现，对外仍然只暴露一个 `ATConnection`：
+/// `connect()`/`send()` 连续失败、或 `receive()` 报出非超时错误达到阈值
+/// 时，标记当前端点不健康并切到下一个，循环回第一个，同时打印切换日志。
+/// 只有一个端点时行为与直接使用该端点完全一致。
+pub struct FailoverATConn {
+    labels: Vec<String>,
+    endpoints: Vec<Box<dyn ATConnection>>,
+    active: usize,
+    consecutive_failures: u32,
+}
+
+impl FailoverATConn {
+    /// `endpoints` 的顺序即故障转移顺序，第一个是主连接目标。
+    pub fn new(endpoints: Vec<(String, Box<dyn ATConnection>)>) -> Self {
+        let (labels, endpoints) = endpoints.into_iter().unzip();
+        Self {
+            labels,
+            endpoints,
+            active: 0,
+            consecutive_failures: 0,
+        }
+    }
+
+    fn note_success(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    fn note_failure(&mut self) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures < MAX_CONSECUTIVE_FAILURES || self.endpoints.len() <= 1 {
+            return;
+        }
+
+        self.endpoints[self.active].disconnect();
+        let next = (self.active + 1) % self.endpoints.len();
+        println!(
+            "[Failover] 端点 \"{}\" 连续失败 {} 次，切换到 \"{}\"",
+            self.labels[self.active], self.consecutive_failures, self.labels[next]
+        );
+        self.active = next;
+        self.consecutive_failures = 0;
+    }
+}
+
+#[async_trait]
+impl ATConnection for FailoverATConn {
+    async fn connect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let result = self.endpoints[self.active].connect().await;
+        match &result {
+            Ok(()) => self.note_success(),
+            Err(_) => self.note_failure(),
+        }
+        result
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let result = self.endpoints[self.active].send(data).await;
+        if result.is_err() {
+            self.note_failure();
+        }
+        result
+    }
+
+    async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let result = self.endpoints[self.active].receive().await;
+        match &result {
+            Ok(_) => self.note_success(),
+            // 读超时是空轮询期间的正常情况（`DEFAULT_READ_TIMEOUT` 只有
+            // 25ms），不能当成链路故障，否则闲时就会被误判下线。
+            Err(e) if e.downcast_ref::<tokio::time::error::Elapsed>().is_some() => {}
+            Err(_) => self.note_failure(),
+        }
+        result
+    }
+
+    fn is_connected(&self) -> bool {
+        self.endpoints[self.active].is_connected()
+    }
+
+    fn disconnect(&mut self) {
+        self.endpoints[self.active].disconnect();
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        for endpoint in &mut self.endpoints {
+            endpoint.set_read_timeout(timeout);
+        }
+    }
+}
+
+// ========== 自动重连封装 ==========
+
+/// `ReconnectingATConn` 的重连策略与重连后的恢复动作。
+#[derive(Debug, Clone)]
+pub struct ReconnectingConfig {
+    /// 重连退避的起始延迟。
+    pub backoff_base: Duration,
+    /// 每次失败后延迟的放大倍数。
+    pub backoff_multiplier: f64,
+    /// 重连退避的上限延迟。
+    pub backoff_max: Duration,
+    /// 连续失败多少次后放弃，`None` 表示无限重试。
+    pub max_attempts: Option<u32>,
+    /// 每次重连成功后按顺序重发的命令，例如 `ATE0`、`AT+CMEE=1`，用来把
+    /// 模组恢复到断线前约定好的模式。
+    pub reinit_commands: Vec<String>,
+}
+
+impl Default for ReconnectingConfig {
+    fn default() -> Self {
+        Self {
+            backoff_base: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            backoff_max: Duration::from_secs(60),
+            max_attempts: None,
+            reinit_commands: Vec::new(),
+        }
+    }
+}
+
+/// 把任意 `ATConnection` 包一层自动重连：`send`/`receive` 遇到真正的 I/O
+/// 错误（读超时除外，那只是 25ms 轮询周期内没有数据，不是故障）时，先
+/// 带抖动的指数退避重连，重连成功后重放 `reinit_commands`、把原先那次
+/// 调用再试一遍；`connect()` 本身也走同一套重连逻辑。链路状态变化通过
+/// 构造时返回的 `broadcast::Receiver<LinkState>` 通知订阅者（WebUI 展示
+/// 断线/恢复）。
+pub struct ReconnectingATConn<C: ATConnection> {
+    inner: C,
+    config: ReconnectingConfig,
+    state_tx: broadcast::Sender<LinkState>,
+}
+
+impl<C: ATConnection> ReconnectingATConn<C> {
+    /// 包装 `inner`，返回句柄以及一个可订阅 `LinkState` 变化的接收端；
+    /// 需要多路订阅时对返回的接收端调用 `.resubscribe()`。
+    pub fn new(inner: C, config: ReconnectingConfig) -> (Self, broadcast::Receiver<LinkState>) {
+        let (state_tx, state_rx) = broadcast::channel(16);
+        (
+            Self {
+                inner,
+                config,
+                state_tx,
+            },
+            state_rx,
+        )
+    }
+
+    async fn reconnect_with_backoff(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let _ = self.state_tx.send(LinkState::Connecting);
+        let mut attempt: u32 = 0;
+
+        loop {
+            match self.inner.connect().await {
+                Ok(()) => {
+                    self.reinit().await;
+                    let _ = self.state_tx.send(LinkState::Up);
+                    return Ok(());
+                }
+                Err(e) => {
+                    attempt = attempt.saturating_add(1);
+                    if let Some(max) = self.config.max_attempts {
+                        if attempt >= max {
+                            let _ = self.state_tx.send(LinkState::Down);
+                            return Err(e);
+                        }
+                    }
+                    sleep(self.backoff_delay(attempt)).await;
+                }
+            }
+        }
+    }
+
+    /// 重连成功后按顺序重发 `reinit_commands`，逐条等待终止码；单条命
+    /// 令失败不中断后续命令，毕竟重连本身已经成功，模式恢复失败不该再
+    /// 把这次重连标记为失败。
+    async fn reinit(&mut self) {
+        for cmd in &self.config.reinit_commands {
+            if self.inner.send(format!("{}\r\n", cmd).as_bytes()).await.is_err() {
+                continue;
+            }
+            let _ = self
+                .inner
+                .receive_response(cmd, Duration::from_millis(500))
+                .await;
+        }
+    }
+
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        let base_ms = self.config.backoff_base.as_millis() as f64;
+        let multiplier = self.config.backoff_multiplier.max(1.0);
+        let exp_ms = base_ms * multiplier.powi(attempt.saturating_sub(1) as i32);
+        let capped_ms = exp_ms.min(self.config.backoff_max.as_millis() as f64);
+
+        let jitter_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64 % 250)
+            .unwrap_or(0);
+
+        Duration::from_millis(capped_ms as u64 + jitter_ms)
+    }
+}
+
+#[async_trait]
+impl<C: ATConnection> ATConnection for ReconnectingATConn<C> {
+    async fn connect(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.reconnect_with_backoff().await
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        match self.inner.send(data).await {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                self.reconnect_with_backoff().await?;
+                self.inner.send(data).await
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        match self.inner.receive().await {
+            Err(e) if e.downcast_ref::<tokio::time::error::Elapsed>().is_none() => {
+                self.reconnect_with_backoff().await?;
+                self.inner.receive().await
+            }
+            other => other,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn disconnect(&mut self) {
+        self.inner.disconnect();
+    }
+
+    fn set_read_timeout(&mut self, timeout: Duration) {
+        self.inner.set_read_timeout(timeout);
+    }
+}
@@ -0,0 +1,55 @@
+// 把分类出的 URC 原始行进一步解析成结构化的模块事件（短信到达、来电、
+// 存储已满、信号强度）。`ATClient::spawn_reader` 在把一行广播到
+// `urc_tx`（给想要原始文本的下游，比如 WebSocket 的 `raw_data`/按主题
+// 订阅）的同时，也会尝试在这里分类出一个 `ModemEvent` 广播到
+// `event_tx`，这样 WebSocket 的类型化通知和 `NotifierHub` 都能直接用结
+// 构化字段，不必各自重新拿原始字符串做一遍判断。
+
+/// 从已经确认是 URC 的一行文本里解析出的结构化事件。
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModemEvent {
+    /// `+CMTI: "SM",<index>` —— 新短信到达，`index` 是存储位置；`+CMT:`
+    /// 这种直接推正文的上报拿不到索引，留 `None`。
+    Sms { index: Option<u32> },
+    /// `+CLIP: "<number>",...` —— 来电，`from` 是主叫号码。
+    Call { from: String },
+    /// 短信存储空间已满的提示。
+    MemoryFull,
+    /// `+CSQ:`/`^HCSQ:` 上报的信号强度，两者格式不同，都只取一个能代表
+    /// 信号好坏的整数，不保证是严格意义上的 dBm RSSI。
+    Signal { rssi: i32 },
+}
+
+/// 尝试把一行 URC 分类成 `ModemEvent`；分类不出来（还没覆盖的 URC 类型）
+/// 返回 `None`，调用方只广播原始文本，不强行凑一个事件。
+pub fn classify_event(line: &str) -> Option<ModemEvent> {
+    if let Some(rest) = line.strip_prefix("+CMTI:") {
+        let index = rest.rsplit(',').next().and_then(|s| s.trim().parse().ok());
+        return Some(ModemEvent::Sms { index });
+    }
+    if line.starts_with("+CMT:") {
+        return Some(ModemEvent::Sms { index: None });
+    }
+
+    if let Some(rest) = line.strip_prefix("+CLIP:") {
+        let from = rest.split(',').next().unwrap_or("").trim().trim_matches('"').to_string();
+        return Some(ModemEvent::Call { from });
+    }
+
+    if line.starts_with("^SMMEMFULL") || line.contains("MEMORY FULL") {
+        return Some(ModemEvent::MemoryFull);
+    }
+
+    if let Some(rest) = line.strip_prefix("+CSQ:") {
+        let rssi = rest.trim().split(',').next().and_then(|s| s.trim().parse().ok());
+        return rssi.map(|rssi| ModemEvent::Signal { rssi });
+    }
+    if let Some(rest) = line.strip_prefix("^HCSQ:") {
+        // `^HCSQ: "<sysmode>",<val1>,...`：紧跟在制式字符串后面的第一个
+        // 数值最接近信号好坏，具体量纲随制式而不同。
+        let rssi = rest.split(',').nth(1).and_then(|s| s.trim().parse().ok());
+        return rssi.map(|rssi| ModemEvent::Signal { rssi });
+    }
+
+    None
+}
@@ -0,0 +1,21 @@
+pub mod client;
+pub mod connection;
+pub mod events;
+pub mod parser;
+pub mod registry;
+pub mod scheduler;
+pub mod session;
+pub mod supervisor;
+pub mod watchdog;
+
+pub use client::{ATClient, ModuleState};
+pub use connection::{
+    ATConnection, FailoverATConn, NetworkATConn, ReconnectingATConn, ReconnectingConfig,
+    SerialATConn, TlsNetworkATConn, TomModemATConn, WebSocketATConn,
+};
+pub use events::ModemEvent;
+pub use registry::ModemRegistry;
+pub use scheduler::{CommandScheduler, Priority};
+pub use session::{ModemSession, SessionPriority, DEFAULT_COMMAND_TIMEOUT};
+pub use supervisor::LinkState;
+pub use watchdog::NetworkWatchdog;
@@ -0,0 +1,188 @@
+use std::fmt;
+
+/// Final result code that closes an in-flight command's response.
+///
+/// `CmeError`/`CmsError` carry the numeric code (`ATClient::bring_up` sends
+/// `AT+CMEE=1` so the modem reports these numerically instead of verbosely)
+/// so callers can branch on the exact failure instead of string-matching.
+/// `Timeout` closes a response the same way a modem-sent final code would,
+/// for commands where the deadline elapsed before one arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalStatus {
+    Ok,
+    Error,
+    CmeError(u16),
+    CmsError(u16),
+    Timeout,
+}
+
+impl fmt::Display for FinalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FinalStatus::Ok => write!(f, "OK"),
+            FinalStatus::Error => write!(f, "ERROR"),
+            FinalStatus::CmeError(code) => write!(f, "+CME ERROR: {}", code),
+            FinalStatus::CmsError(code) => write!(f, "+CMS ERROR: {}", code),
+            FinalStatus::Timeout => write!(f, "TIMEOUT"),
+        }
+    }
+}
+
+/// One line classified out of the modem's byte stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    /// Echo of the command we just sent.
+    Echo(String),
+    /// Unsolicited result code, not part of any pending command's response.
+    Urc(String),
+    /// An information/data line belonging to the response body of the active command.
+    Data(String),
+    /// A final result code closing the active command.
+    Final(FinalStatus),
+    /// The `>` SMS prompt: the modem is waiting for a PDU body terminated by Ctrl-Z.
+    Prompt,
+}
+
+// URC prefixes the modem can emit without being asked, as configured by
+// `bring_up` (AT+CNMI, AT+CLIP) and the firmware's own status reports.
+const URC_PREFIXES: &[&str] = &[
+    "+CMTI:", "+CMT:", "^SMMEMFULL", "+CLIP:", "RING", "+CREG:", "+CEREG:", "+CGREG:", "^HCSQ",
+    "^SIMST", "^CEND", "^RSSI", "^MODE", "^BOOT",
+];
+
+fn is_urc_line(line: &str) -> bool {
+    URC_PREFIXES.iter().any(|p| line.starts_with(p))
+}
+
+/// 把一条 URC 行粗略归到一个主题上，供 MQTT 状态面、WebSocket 订阅过滤
+/// 这类下游按主题而不是按具体前缀做判断；新增前缀只需要在这里添加一个
+/// 分支，不必到每个下游各改一遍。
+pub fn urc_topic(line: &str) -> &'static str {
+    if line.starts_with("+CMTI:") || line.starts_with("+CMT:") || line.starts_with("^SMMEMFULL") {
+        "sms"
+    } else if line.starts_with("+CLIP:") || line.starts_with("RING") || line.starts_with("^CEND") {
+        "call"
+    } else if line.starts_with("^HCSQ") || line.starts_with("^RSSI") {
+        "signal"
+    } else if line.starts_with("+CREG:")
+        || line.starts_with("+CEREG:")
+        || line.starts_with("+CGREG:")
+        || line.starts_with("^SIMST")
+    {
+        "registration"
+    } else {
+        "misc"
+    }
+}
+
+/// Classify a single already-trimmed line relative to the command currently
+/// awaiting a response. `echo_cmd` is the command text we sent, without CRLF.
+pub fn classify_line(line: &str, echo_cmd: &str) -> Line {
+    let trimmed = line.trim();
+
+    if trimmed == "OK" {
+        return Line::Final(FinalStatus::Ok);
+    }
+    if trimmed == "ERROR" || trimmed == "NO CARRIER" {
+        return Line::Final(FinalStatus::Error);
+    }
+    if let Some(rest) = trimmed.strip_prefix("+CME ERROR:") {
+        return Line::Final(FinalStatus::CmeError(rest.trim().parse().unwrap_or(0)));
+    }
+    if let Some(rest) = trimmed.strip_prefix("+CMS ERROR:") {
+        return Line::Final(FinalStatus::CmsError(rest.trim().parse().unwrap_or(0)));
+    }
+    if !echo_cmd.is_empty() && trimmed == echo_cmd {
+        return Line::Echo(trimmed.to_string());
+    }
+    if trimmed == ">" {
+        return Line::Prompt;
+    }
+    // A command's own information response (`+CEREG: 0,1` to `AT+CEREG?`)
+    // shares its prefix with the URC of the same name; it belongs to the
+    // pending command's body, not to `urc_tx`.
+    let cmd_name = echo_cmd
+        .get(2..)
+        .unwrap_or("")
+        .split(|c: char| c == '=' || c == '?')
+        .next()
+        .unwrap_or("");
+    let answers_cmd = !cmd_name.is_empty()
+        && trimmed
+            .strip_prefix(cmd_name)
+            .map_or(false, |rest| rest.starts_with(':'));
+    if is_urc_line(trimmed) && !answers_cmd {
+        return Line::Urc(trimmed.to_string());
+    }
+    Line::Data(trimmed.to_string())
+}
+
+/// Accumulates raw bytes from the modem and splits them into complete,
+/// trimmed lines as `\r\n`/`\n`-terminated records become available,
+/// buffering any trailing partial line across calls. This also covers the
+/// `>` SMS prompt, which firmwares send without a trailing newline.
+#[derive(Default)]
+pub struct LineDigester {
+    buf: String,
+}
+
+impl LineDigester {
+    pub fn new() -> Self {
+        Self { buf: String::new() }
+    }
+
+    /// Feed newly received bytes, returning every complete line they close
+    /// out. A lone `>` prompt (no newline) is also surfaced immediately,
+    /// since the modem expects a PDU body to follow without further input.
+    pub fn feed(&mut self, chunk: &str) -> Vec<String> {
+        self.buf.push_str(chunk);
+        let mut lines = Vec::new();
+
+        while let Some(pos) = self.buf.find('\n') {
+            let raw: String = self.buf.drain(..=pos).collect();
+            let line = raw.trim().to_string();
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+
+        if self.buf.trim() == ">" {
+            lines.push(self.buf.trim().to_string());
+            self.buf.clear();
+        }
+
+        lines
+    }
+}
+
+/// The collected outcome of one in-flight command.
+#[derive(Debug, Clone)]
+pub struct AtResponse {
+    pub lines: Vec<String>,
+    pub status: FinalStatus,
+}
+
+impl AtResponse {
+    pub fn is_ok(&self) -> bool {
+        matches!(self.status, FinalStatus::Ok)
+    }
+
+    /// Render the response body the way `send_command` callers expect it:
+    /// data lines joined by `\n`, final code stripped.
+    pub fn body(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+/// The outcome of `ATConnection::receive_response`: the framed command
+/// response plus any URC lines that arrived while waiting for it. Callers
+/// that go through `ATClient::spawn_reader` never see this directly (it
+/// already splits URCs off onto `urc_tx`); this is for direct `ATConnection`
+/// users that have no reader task of their own to hand URCs to, like
+/// `ReconnectingATConn::reinit` replaying commands right after a fresh
+/// `connect()`, before any reader has been wired back up.
+#[derive(Debug, Clone)]
+pub struct FramedResponse {
+    pub response: AtResponse,
+    pub urcs: Vec<String>,
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::at::scheduler::CommandScheduler;
+use crate::at::ATClient;
+use crate::config::{Config, DEFAULT_MODEM_ID};
+
+/// 按配置里的 ID 持有多个 `ATClient`，让一台 OpenWrt 设备通过单一
+/// WebSocket 端点同时前端挂载多个 MT5700/5G 加密狗。每个模块的
+/// `CommandScheduler` 与对应的 `ATClient` 一一绑定，是驱动这个模块的
+/// 唯一入口：背景心跳、调度任务、交互命令都经它按优先级排队，不再各自
+/// 直接抢 `ATClient` 内部的连接锁。
+pub struct ModemRegistry {
+    clients: HashMap<String, Arc<ATClient>>,
+    schedulers: HashMap<String, Arc<CommandScheduler>>,
+}
+
+impl ModemRegistry {
+    /// 为 `config.at_configs` 里的每个模块各建一个 `ATClient` 及其配套的
+    /// `CommandScheduler`。任意一个模块初始化失败都会中止整体启动，避免
+    /// 半套注册表悄悄上线。
+    pub fn build(config: &Arc<Config>) -> Result<Self, Box<dyn Error>> {
+        let mut clients = HashMap::new();
+        let mut schedulers = HashMap::new();
+        for (id, at_config) in &config.at_configs {
+            let client = ATClient::new(at_config, config.module_timing)
+                .map_err(|e| format!("模块 \"{}\" 初始化失败: {}", id, e))?;
+            let client = Arc::new(client);
+            let scheduler = CommandScheduler::spawn(client.clone(), config.scheduler_config);
+            clients.insert(id.clone(), client);
+            schedulers.insert(id.clone(), scheduler);
+        }
+        Ok(Self { clients, schedulers })
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<ATClient>> {
+        self.clients.get(id).cloned()
+    }
+
+    /// 拿到模块对应的命令调度器，驱动该模块的命令都应该经它提交。
+    pub fn scheduler(&self, id: &str) -> Option<Arc<CommandScheduler>> {
+        self.schedulers.get(id).cloned()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.clients.keys().cloned().collect()
+    }
+
+    /// 未显式指定 `modem` 字段的 WebSocket 请求落在这个 ID 上。
+    pub fn default_id(&self) -> String {
+        if self.clients.contains_key(DEFAULT_MODEM_ID) {
+            DEFAULT_MODEM_ID.to_string()
+        } else {
+            self.clients
+                .keys()
+                .next()
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_MODEM_ID.to_string())
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Arc<ATClient>)> {
+        self.clients.iter()
+    }
+}
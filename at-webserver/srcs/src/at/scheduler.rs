@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::interval;
+
+use crate::at::client::ATClient;
+use crate::at::parser::{AtResponse, FinalStatus};
+use crate::config::SchedulerConfig;
+
+/// 命令派发优先级，数值越大越先被派发（声明顺序即比较顺序）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// 心跳/周期性状态轮询这类背景流量，被令牌桶限速，绝不能抢占下面两档。
+    Keepalive,
+    /// 定时锁频、自动飞行模式这类后台调度任务。
+    Scheduler,
+    /// WebSocket/MQTT 上由人或外部系统直接触发的命令，优先级最高。
+    Interactive,
+}
+
+struct QueuedCommand {
+    priority: Priority,
+    seq: u64,
+    command: String,
+    reply: oneshot::Sender<Result<AtResponse, Box<dyn Error + Send + Sync>>>,
+}
+
+impl PartialEq for QueuedCommand {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+impl Eq for QueuedCommand {}
+
+impl Ord for QueuedCommand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` 是大顶堆：优先级高的排在前面；同优先级内按提交顺序
+        // 先进先出，所以 seq 比较要反过来（seq 越小越该先出队，视为“更大”）。
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedCommand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 排在 `ATClient` 前面的命令调度器：所有调用方把 `(Priority, String)` 提交
+/// 进来，换回一个装着结果的 oneshot；唯一的后台 worker 按优先级从高到低
+/// 排空队列，并用令牌桶把 `Keepalive` 这一档背景轮询限速，避免它在 UI
+/// 突发请求时抢占真正要紧的命令，也避免它反过来饿死自己之外的任何一档。
+pub struct CommandScheduler {
+    tx: mpsc::UnboundedSender<QueuedCommand>,
+}
+
+impl CommandScheduler {
+    /// 起一个后台 worker 独占地驱动 `client`，返回可供多处克隆持有的句柄。
+    pub fn spawn(client: Arc<ATClient>, config: SchedulerConfig) -> Arc<Self> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<QueuedCommand>();
+
+        tokio::spawn(async move {
+            let mut heap: BinaryHeap<QueuedCommand> = BinaryHeap::new();
+            let mut seq: u64 = 0;
+            let burst = config.burst.max(1) as f64;
+            let mut tokens = burst;
+            let refill_per_tick = config.rate_per_sec.max(0.0) * 0.2;
+            let mut refill_timer = interval(Duration::from_millis(200));
+
+            loop {
+                tokio::select! {
+                    biased;
+                    maybe_cmd = rx.recv() => {
+                        match maybe_cmd {
+                            Some(mut cmd) => {
+                                seq += 1;
+                                cmd.seq = seq;
+                                heap.push(cmd);
+                            }
+                            None => break,
+                        }
+                    }
+                    _ = refill_timer.tick() => {
+                        tokens = (tokens + refill_per_tick).min(burst);
+                    }
+                }
+
+                while let Some(top) = heap.peek() {
+                    if top.priority == Priority::Keepalive && tokens < 1.0 {
+                        // 队首只剩被限速的背景轮询，且令牌不够：先回到上面的
+                        // select，等下一次令牌刷新或者更高优先级的命令到达。
+                        break;
+                    }
+
+                    let cmd = heap.pop().expect("heap 刚 peek 过，pop 一定成功");
+                    if cmd.priority == Priority::Keepalive {
+                        tokens -= 1.0;
+                    }
+
+                    let result = client.send_command_typed(cmd.command).await;
+                    let _ = cmd.reply.send(result);
+                }
+            }
+        });
+
+        Arc::new(Self { tx })
+    }
+
+    /// 提交一条命令并等待带类型终止码的响应，`priority` 决定它在队列里排
+    /// 在谁前面。
+    pub async fn submit(
+        &self,
+        priority: Priority,
+        command: String,
+    ) -> Result<AtResponse, Box<dyn Error + Send + Sync>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(QueuedCommand {
+                priority,
+                seq: 0,
+                command,
+                reply: reply_tx,
+            })
+            .map_err(|_| "命令调度器已停止".to_string())?;
+
+        reply_rx.await.map_err(|_| "命令调度器未返回结果".to_string())?
+    }
+
+    /// 与 `ATClient::send_command` 对齐的字符串接口：成功返回响应正文，
+    /// 终止码非 `OK` 时返回错误。
+    pub async fn submit_str(
+        &self,
+        priority: Priority,
+        command: String,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let response = self.submit(priority, command).await?;
+        if response.is_ok() {
+            Ok(response.body())
+        } else {
+            Err(match response.status {
+                FinalStatus::Error => "ERROR".into(),
+                other => other.to_string().into(),
+            })
+        }
+    }
+}
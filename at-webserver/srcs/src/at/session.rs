@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::{sleep_until, Instant};
+
+use crate::at::connection::ATConnection;
+use crate::at::parser::{classify_line, AtResponse, FinalStatus, Line, LineDigester};
+
+/// 单条命令从写出去到必须收到终止码的最长等待时间，超时就地判给
+/// `FinalStatus::Timeout`，既避免一条没人回应的命令卡住它后面所有排队
+/// 的命令，也保证 `shutdown()` 的优雅收尾不会因为链路卡死而永远等下去。
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `ModemSession::execute` 的排队优先级，数值越大越先被派发。与
+/// `scheduler::Priority`（背景轮询/调度任务/交互命令三档，外加令牌桶限
+/// 速）是两回事：`ModemSession` 直接坐在一条 `ATConnection` 之上，不知
+/// 道、也不关心上层 `CommandScheduler`/`ATClient` 的路由和限速策略,只
+/// 负责把同一条链路上的命令按优先级派发、按先进先出的顺序把响应对号入
+/// 座。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SessionPriority {
+    Low,
+    Normal,
+    High,
+}
+
+struct QueuedRequest {
+    priority: SessionPriority,
+    seq: u64,
+    command: String,
+    reply: oneshot::Sender<Result<AtResponse, Box<dyn Error + Send + Sync>>>,
+}
+
+impl PartialEq for QueuedRequest {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.seq == other.seq
+    }
+}
+impl Eq for QueuedRequest {}
+
+impl Ord for QueuedRequest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` 是大顶堆：优先级高的排前面；同优先级内按提交顺序
+        // 先进先出，所以 seq 比较要反过来（seq 越小越该先出队，视为“更
+        // 大”）。
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedRequest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+enum SessionMsg {
+    Submit(QueuedRequest),
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// 直接架在一条 `ATConnection` 之上的请求/响应会话：唯一的后台任务独占
+/// 持有连接，按优先级把排队的命令写出去，再按先进先出的顺序把读到的终
+/// 止码对应回最早还没收到响应的那条命令；期间出现的 URC 行广播给所有订
+/// 阅者。和 `ATClient`（同一时刻只认一条在途命令）不同，这里允许多条命
+/// 令背靠背写出去、稍后按顺序认领响应，给那些支持管道化、不等上一条回
+/// 应就能接收下一条命令的链路留出空间。
+pub struct ModemSession {
+    tx: mpsc::UnboundedSender<SessionMsg>,
+    urc_tx: broadcast::Sender<String>,
+}
+
+impl ModemSession {
+    /// 起一个后台任务独占地驱动 `conn`，返回可供多处克隆持有的句柄。
+    /// `command_timeout` 是每条命令从写出去到必须收到终止码的最长等待,
+    /// 超时的命令按 `FinalStatus::Timeout` 结束，不使用默认值时传
+    /// `DEFAULT_COMMAND_TIMEOUT`。
+    pub fn spawn(mut conn: Box<dyn ATConnection>, command_timeout: Duration) -> Arc<Self> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<SessionMsg>();
+        let (urc_tx, _) = broadcast::channel(64);
+        let urc_tx_task = urc_tx.clone();
+
+        tokio::spawn(async move {
+            let mut heap: BinaryHeap<QueuedRequest> = BinaryHeap::new();
+            let mut in_flight: VecDeque<(
+                String,
+                Instant,
+                oneshot::Sender<Result<AtResponse, Box<dyn Error + Send + Sync>>>,
+            )> = VecDeque::new();
+            let mut pending_lines: Vec<String> = Vec::new();
+            let mut digester = LineDigester::new();
+            let mut seq: u64 = 0;
+            let mut draining = false;
+            let mut shutdown_ack: Option<oneshot::Sender<()>> = None;
+            let mut hard_error: Option<Box<dyn Error + Send + Sync>> = None;
+
+            'drive: loop {
+                while let Some(req) = heap.pop() {
+                    if let Err(e) = conn.send(format!("{}\r\n", req.command).as_bytes()).await {
+                        let _ = req.reply.send(Err(e));
+                        continue;
+                    }
+                    in_flight.push_back((req.command, Instant::now() + command_timeout, req.reply));
+                }
+
+                // 已经进入优雅关闭、且所有在途请求都已经收到终止码（或超
+                // 时判负）：不再等待新的提交，直接收尾断开连接。
+                if draining && in_flight.is_empty() {
+                    break;
+                }
+
+                let next_deadline = in_flight.front().map(|(_, deadline, _)| *deadline);
+
+                tokio::select! {
+                    biased;
+                    msg = rx.recv(), if !draining => {
+                        // 把此刻已经排在通道里的其它提交一并收进堆里，优先级才
+                        // 会在它们之间生效。
+                        let mut next = msg;
+                        loop {
+                            match next {
+                                Some(SessionMsg::Submit(mut req)) => {
+                                    seq += 1;
+                                    req.seq = seq;
+                                    heap.push(req);
+                                }
+                                Some(SessionMsg::Shutdown(ack)) => {
+                                    draining = true;
+                                    shutdown_ack = Some(ack);
+                                    break;
+                                }
+                                None => {
+                                    draining = true;
+                                    break;
+                                }
+                            }
+                            match rx.try_recv() {
+                                Ok(m) => next = Some(m),
+                                Err(_) => break,
+                            }
+                        }
+                    }
+                    _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                        if let Some((_, _, reply)) = in_flight.pop_front() {
+                            let lines = std::mem::take(&mut pending_lines);
+                            let _ = reply.send(Ok(AtResponse { lines, status: FinalStatus::Timeout }));
+                        }
+                    }
+                    bytes = conn.receive() => {
+                        match bytes {
+                            Ok(bytes) if !bytes.is_empty() => {
+                                let chunk = String::from_utf8_lossy(&bytes).to_string();
+                                let echo_cmd = in_flight.front().map(|(cmd, _, _)| cmd.clone()).unwrap_or_default();
+                                for line in digester.feed(&chunk) {
+                                    match classify_line(&line, &echo_cmd) {
+                                        Line::Echo(_) | Line::Prompt => {}
+                                        Line::Urc(urc) => {
+                                            let _ = urc_tx_task.send(urc);
+                                        }
+                                        Line::Data(data) => pending_lines.push(data),
+                                        Line::Final(status) => {
+                                            if let Some((_, _, reply)) = in_flight.pop_front() {
+                                                let lines = std::mem::take(&mut pending_lines);
+                                                let _ = reply.send(Ok(AtResponse { lines, status }));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            // 空读：通常是读超时到点但没有新字节，不代表链路
+                            // 出了问题，回到循环顶继续等。
+                            Ok(_) => {}
+                            // `Elapsed` 只是 `receive()` 内部的轮询周期到了，
+                            // 同样不是故障；其它错误说明链路本身断了，不能
+                            // 再傻等下一次 `receive()`（否则要么死循环空转，
+                            // 要么在 draining 时永远等不到 in_flight 清空），
+                            // 直接结束驱动循环、把剩下的请求都判失败。
+                            Err(e) if e.downcast_ref::<tokio::time::error::Elapsed>().is_some() => {}
+                            Err(e) => {
+                                hard_error = Some(e);
+                                break 'drive;
+                            }
+                        }
+                    }
+                }
+            }
+
+            let fail_msg = hard_error
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "连接已断开".to_string());
+            for (_, _, reply) in in_flight.drain(..) {
+                let _ = reply.send(Err(fail_msg.clone().into()));
+            }
+            for req in heap.into_iter() {
+                let _ = req.reply.send(Err(fail_msg.clone().into()));
+            }
+
+            if let Some(ack) = shutdown_ack {
+                let _ = ack.send(());
+            }
+            conn.disconnect();
+        });
+
+        Arc::new(Self { tx, urc_tx })
+    }
+
+    /// 提交一条命令并等待它的终止码，`priority` 决定它在队列里排在谁前
+    /// 面；同一优先级内先提交的先派发。
+    pub async fn execute(
+        &self,
+        priority: SessionPriority,
+        command: String,
+    ) -> Result<AtResponse, Box<dyn Error + Send + Sync>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(SessionMsg::Submit(QueuedRequest {
+                priority,
+                seq: 0,
+                command,
+                reply: reply_tx,
+            }))
+            .map_err(|_| "会话已停止".to_string())?;
+
+        reply_rx.await.map_err(|_| "会话未返回结果".to_string())?
+    }
+
+    /// 订阅这条连接上的 URC：每次调用都拿到一个独立的 `broadcast`
+    /// 接收端，互不影响彼此的消费进度。
+    pub fn subscribe_urc(&self) -> broadcast::Receiver<String> {
+        self.urc_tx.subscribe()
+    }
+
+    /// 优雅关闭：停止接受新提交，等在途请求都收到终止码（或连接出错提
+    /// 前结束）之后再断开底层连接。
+    pub async fn shutdown(&self) {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        if self.tx.send(SessionMsg::Shutdown(ack_tx)).is_ok() {
+            let _ = ack_rx.await;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tokio::time::{sleep, timeout};
+
+use crate::at::client::ATClient;
+use crate::at::connection::ATConnection;
+
+/// 链路状态，通过 `broadcast` 通知 WebSocket 层等订阅者。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    Connecting,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// 两次健康探测之间的间隔。
+    pub probe_interval: Duration,
+    /// 等待探测命令响应的超时。
+    pub probe_timeout: Duration,
+    /// 重连退避的起始延迟。
+    pub backoff_base: Duration,
+    /// 重连退避的上限延迟。
+    pub backoff_max: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval: Duration::from_secs(15),
+            probe_timeout: Duration::from_millis(800),
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+        }
+    }
+}
+
+/// 启动连接监督任务：跟踪链路状态，定期用一条轻量 `AT` 命令探活，探测失败
+/// 或本就处于断开状态时以带抖动的指数退避重新 `connect()`。
+///
+/// 探活本身借道 `client.send_command_typed`，不直接碰 `client.conn`：
+/// `ATClient::spawn_reader` 是唯一允许从连接上 `receive()` 的地方（见它
+/// 自己的文档），这里要是另起一次裸 `send`/`receive_response`，会和
+/// `spawn_reader` 抢同一条连接——探测窗口内到达的 URC 被探测读走就再也到
+/// 不了 `urc_tx`，真正命令的响应行也可能被探测误当成自己的响应吞掉，
+/// 导致那条命令莫名其妙超时。走 `send_command_typed` 就是走跟其它命令完
+/// 全一样的登记/回传通道，不会有这个问题。
+pub fn spawn(client: Arc<ATClient>, cfg: SupervisorConfig) -> broadcast::Receiver<LinkState> {
+    let (tx, rx) = broadcast::channel(16);
+
+    tokio::spawn(async move {
+        let mut attempt: u32 = 0;
+        let mut last_state: Option<LinkState> = None;
+
+        loop {
+            let connected = client.conn.lock().await.is_connected();
+
+            if !connected {
+                emit(&tx, &mut last_state, LinkState::Connecting);
+
+                let delay = backoff_delay(&cfg, attempt);
+                sleep(delay).await;
+
+                let result = client.conn.lock().await.connect().await;
+                match result {
+                    Ok(()) => {
+                        println!("[Supervisor] 连接已建立");
+                        attempt = 0;
+                        emit(&tx, &mut last_state, LinkState::Up);
+                    }
+                    Err(e) => {
+                        attempt = attempt.saturating_add(1);
+                        println!("[Supervisor] 连接失败 (第 {} 次): {}", attempt, e);
+                    }
+                }
+                continue;
+            }
+
+            if probe(&client, cfg.probe_timeout).await {
+                attempt = 0;
+                emit(&tx, &mut last_state, LinkState::Up);
+                sleep(cfg.probe_interval).await;
+            } else {
+                println!("[Supervisor] 健康探测失败，断开并准备重连");
+                client.conn.lock().await.disconnect();
+                emit(&tx, &mut last_state, LinkState::Down);
+            }
+        }
+    });
+
+    rx
+}
+
+fn emit(tx: &broadcast::Sender<LinkState>, last_state: &mut Option<LinkState>, state: LinkState) {
+    if *last_state != Some(state) {
+        *last_state = Some(state);
+        let _ = tx.send(state);
+    }
+}
+
+async fn probe(client: &Arc<ATClient>, probe_timeout: Duration) -> bool {
+    match timeout(probe_timeout, client.send_command_typed("AT\r\n".to_string())).await {
+        Ok(Ok(response)) => response.is_ok(),
+        Ok(Err(_)) | Err(_) => false,
+    }
+}
+
+/// 指数退避 + 抖动，避免大量重连请求同时发生（thundering herd）。
+fn backoff_delay(cfg: &SupervisorConfig, attempt: u32) -> Duration {
+    let base_ms = cfg.backoff_base.as_millis() as u64;
+    let exp_ms = base_ms.saturating_mul(1u64 << attempt.min(6));
+    let capped_ms = exp_ms.min(cfg.backoff_max.as_millis() as u64);
+
+    let jitter_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64 % 250)
+        .unwrap_or(0);
+
+    Duration::from_millis(capped_ms + jitter_ms)
+}
@@ -0,0 +1,124 @@
+// 无服务自愈看门狗：定期探测驻网状态，长时间没有服务时按「先切一次飞行
+// 模式、仍不行再走完整开机自检」的顺序逐级升级恢复，不依赖人工重启设备。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{interval, sleep, Instant};
+
+use crate::at::scheduler::{CommandScheduler, Priority};
+use crate::at::ATClient;
+use crate::config::WatchdogConfig;
+
+/// 一个正在运行的看门狗任务的句柄：只暴露只读计数器，实际状态全部留在
+/// 后台任务的局部变量里，避免额外的锁。
+pub struct NetworkWatchdog {
+    outages: AtomicU64,
+    recoveries: AtomicU64,
+}
+
+impl NetworkWatchdog {
+    /// 为 `client` 起一个看门狗任务，驻网探测和一级恢复（CFUN 切换）的命令
+    /// 都经 `scheduler` 以 `Priority::Scheduler` 提交，和定时锁频、自动飞行
+    /// 模式共用同一套优先级排队；二级恢复直接调用 `client.bring_up()`，它
+    /// 下发的命令不经过 `scheduler`。
+    pub fn spawn(client: Arc<ATClient>, scheduler: Arc<CommandScheduler>, config: WatchdogConfig) -> Arc<Self> {
+        let watchdog = Arc::new(Self {
+            outages: AtomicU64::new(0),
+            recoveries: AtomicU64::new(0),
+        });
+        let handle = watchdog.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = interval(Duration::from_secs(config.check_interval_secs.max(1)));
+            let mut last_service = Instant::now();
+            let mut in_outage = false;
+            let mut recovery_attempt: u32 = 0;
+
+            loop {
+                ticker.tick().await;
+
+                if poll_registration(&scheduler).await {
+                    if in_outage {
+                        println!("[Watchdog] 网络已恢复");
+                        let _ = client.urc_tx.send("^WATCHDOG: RECOVERED".to_string());
+                        handle.recoveries.fetch_add(1, Ordering::Relaxed);
+                    }
+                    last_service = Instant::now();
+                    in_outage = false;
+                    recovery_attempt = 0;
+                    continue;
+                }
+
+                let outage_secs = last_service.elapsed().as_secs();
+                if outage_secs < config.no_service_timeout_secs {
+                    continue;
+                }
+
+                if !in_outage {
+                    in_outage = true;
+                    handle.outages.fetch_add(1, Ordering::Relaxed);
+                    println!("[Watchdog] 已连续 {} 秒无服务，开始自愈", outage_secs);
+                    let _ = client.urc_tx.send("^WATCHDOG: LOST_SERVICE".to_string());
+                }
+
+                recovery_attempt += 1;
+                println!("[Watchdog] 第 {} 次恢复尝试", recovery_attempt);
+                let _ = client
+                    .urc_tx
+                    .send(format!("^WATCHDOG: RECOVERY_ATTEMPT,{}", recovery_attempt));
+
+                if recovery_attempt == 1 {
+                    // 一级恢复：切一次飞行模式，促使基带重新搜网/附着。
+                    let _ = scheduler
+                        .submit_str(Priority::Scheduler, "AT+CFUN=0\r\n".to_string())
+                        .await;
+                    sleep(Duration::from_secs(client.timing.cfun_off_settle_secs)).await;
+                    let _ = scheduler
+                        .submit_str(Priority::Scheduler, "AT+CFUN=1\r\n".to_string())
+                        .await;
+                    sleep(Duration::from_secs(client.timing.cfun_on_settle_secs)).await;
+                } else {
+                    // CFUN 切换仍未见效：走一遍完整的开机自检流程。
+                    println!("[Watchdog] CFUN 恢复未见效，执行完整重新初始化");
+                    if let Err(e) = client.bring_up().await {
+                        println!("[Watchdog] 重新初始化失败: {}", e);
+                    }
+                }
+
+                // 给本轮恢复留出反应时间，避免模块还没来得及重新驻网就被
+                // 判定失败、立刻又触发下一轮升级。
+                sleep(Duration::from_secs(config.recovery_grace_secs)).await;
+            }
+        });
+
+        watchdog
+    }
+
+    pub fn outages(&self) -> u64 {
+        self.outages.load(Ordering::Relaxed)
+    }
+
+    pub fn recoveries(&self) -> u64 {
+        self.recoveries.load(Ordering::Relaxed)
+    }
+}
+
+/// 先查 `AT+CEREG?`（4G/5G NAS 注册），查不到服务再退回 `AT+CREG?`（2G/3G
+/// CS 域注册），两者中任一显示 `0,1`/`0,5` 就算有服务。
+async fn poll_registration(scheduler: &Arc<CommandScheduler>) -> bool {
+    let cereg_ok = scheduler
+        .submit_str(Priority::Scheduler, "AT+CEREG?\r\n".to_string())
+        .await
+        .map(|body| body.contains("0,1") || body.contains("0,5"))
+        .unwrap_or(false);
+    if cereg_ok {
+        return true;
+    }
+
+    scheduler
+        .submit_str(Priority::Scheduler, "AT+CREG?\r\n".to_string())
+        .await
+        .map(|body| body.contains("0,1") || body.contains("0,5"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,88 @@
+// fail2ban 式的 WebSocket 认证防护：按来源 IP 记录认证失败次数，滑动窗口
+// 内达到阈值即封禁，封禁时长随失败轮次指数升级，拖慢不断重试密钥的暴力
+// 破解。状态全部留在内存里，进程重启即清零。
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::BanConfig;
+
+/// 单个来源 IP 的状态：滑动窗口内的失败时间戳，累计触发过的封禁轮次
+/// （用于指数升级时长），以及当前封禁到期时间。
+struct FailureRecord {
+    failures: Vec<Instant>,
+    ban_rounds: u32,
+    banned_until: Option<Instant>,
+}
+
+/// WebSocket 认证失败追踪与自动封禁，可在多个连接间共享。
+pub struct BanGuard {
+    config: BanConfig,
+    records: Mutex<HashMap<IpAddr, FailureRecord>>,
+}
+
+impl BanGuard {
+    pub fn new(config: BanConfig) -> Self {
+        Self {
+            config,
+            records: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// accept 循环里先调用这个：已被封禁的 IP 直接拒绝，不必再起
+    /// `handle_connection`、更不必走到认证那一步。
+    pub fn is_banned(&self, ip: IpAddr) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let records = self.records.lock().unwrap();
+        records
+            .get(&ip)
+            .and_then(|r| r.banned_until)
+            .map(|until| Instant::now() < until)
+            .unwrap_or(false)
+    }
+
+    /// 记录一次认证失败；滑动窗口内的失败次数达到 `threshold` 就（重新）
+    /// 触发封禁，时长为 `base_ban_secs * 2^ban_rounds`，封顶 `max_ban_secs`。
+    pub fn record_failure(&self, ip: IpAddr) {
+        if !self.config.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(self.config.window_secs.max(1));
+
+        let mut records = self.records.lock().unwrap();
+        let record = records.entry(ip).or_insert_with(|| FailureRecord {
+            failures: Vec::new(),
+            ban_rounds: 0,
+            banned_until: None,
+        });
+
+        record.failures.retain(|t| now.duration_since(*t) < window);
+        record.failures.push(now);
+
+        if record.failures.len() as u32 >= self.config.threshold {
+            let ban_secs = self
+                .config
+                .base_ban_secs
+                .saturating_mul(1u64 << record.ban_rounds.min(16))
+                .min(self.config.max_ban_secs);
+            record.banned_until = Some(now + Duration::from_secs(ban_secs));
+            record.ban_rounds += 1;
+            record.failures.clear();
+            println!(
+                "[Ban] IP {} 认证失败已达 {} 次，封禁 {} 秒 (第 {} 次封禁)",
+                ip, self.config.threshold, ban_secs, record.ban_rounds
+            );
+        }
+    }
+
+    /// 认证成功后清空该 IP 的失败记录，避免偶尔手误的正常用户被连坐。
+    pub fn record_success(&self, ip: IpAddr) {
+        let mut records = self.records.lock().unwrap();
+        records.remove(&ip);
+    }
+}
@@ -5,35 +5,124 @@ use std::process::Command;
 
 // 默认配置常量
 pub const DEFAULT_CONFIG_JSON: &str = r#"{
-    "AT_CONFIG": {
-        "TYPE": "NETWORK",
-        "NETWORK": { "HOST": "192.168.8.1", "PORT": 20249, "TIMEOUT": 30 },
-        "SERIAL": { 
-            "PORT": "COM6", 
-            "BAUDRATE": 115200, 
-            "TIMEOUT": 30,
-            "METHOD": "TOM_MODEM",
-            "FEATURE": "UBUS"
+    "AT_CONFIGS": {
+        "default": {
+            "TYPE": "NETWORK",
+            "NETWORK": {
+                "HOST": "192.168.8.1",
+                "PORT": 20249,
+                "TIMEOUT": 30,
+                "TLS": {
+                    "ENABLED": false,
+                    "CA_PATH": "",
+                    "CLIENT_CERT_PATH": "",
+                    "CLIENT_KEY_PATH": "",
+                    "INSECURE_SKIP_VERIFY": false
+                }
+            },
+            "SERIAL": {
+                "PORT": "COM6",
+                "BAUDRATE": 115200,
+                "TIMEOUT": 30,
+                "METHOD": "TOM_MODEM",
+                "FEATURE": "UBUS"
+            },
+            "WS_RELAY": {
+                "URL": "",
+                "TOKEN": "",
+                "PING_INTERVAL_SECS": 30
+            },
+            "MODEL": "MT5700M"
         }
     },
     "WEBSOCKET_CONFIG": {
         "IPV4": { "HOST": "0.0.0.0", "PORT": 8765 },
         "IPV6": { "HOST": "::", "PORT": 8765 },
-        "AUTH_KEY": ""
+        "AUTH_KEY": "",
+        "TLS": { "ENABLED": false, "CERT_PATH": "", "KEY_PATH": "" }
+    },
+    "WEBSOCKET_BAN_CONFIG": {
+        "ENABLED": true,
+        "THRESHOLD": 5,
+        "WINDOW_SECS": 300,
+        "BASE_BAN_SECS": 60,
+        "MAX_BAN_SECS": 3600
     },
     "NOTIFICATION_CONFIG": {
-        "WECHAT_WEBHOOK": "",
         "LOG_FILE": "",
-        "NOTIFICATION_TYPES": {
-            "SMS": true,
-            "CALL": true,
-            "MEMORY_FULL": true,
-            "SIGNAL": true
-        }
+        "BACKENDS": [
+            {
+                "KIND": "WECHAT_WORK",
+                "ENABLED": false,
+                "URL": "",
+                "BOT_TOKEN": "",
+                "CHAT_ID": "",
+                "BROKER": "",
+                "TOPIC": "",
+                "EVENTS": { "SMS": true, "CALL": true, "MEMORY_FULL": true, "SIGNAL": true, "SCHEDULE_LOCK": true },
+                "RATE_LIMIT_PER_MIN": 20
+            }
+        ]
     },
     "SCHEDULE_AIRPLANE_CONFIG": {
         "ENABLED": false,
-        "ACTION_TIME": "8:00"
+        "ACTION_TIME": "8:00",
+        "SIGNAL_LOSS_MINUTES": 0
+    },
+    "SCHEDULE_CONFIG": {
+        "ENABLED": false,
+        "CHECK_INTERVAL": 60,
+        "TIMEOUT": 180,
+        "UNLOCK_LTE": true,
+        "UNLOCK_NR": true,
+        "TOGGLE_AIRPLANE": true,
+        "NIGHT_ENABLED": true,
+        "NIGHT_START": "22:00",
+        "NIGHT_END": "06:00",
+        "NIGHT_LTE_TYPE": 0,
+        "NIGHT_LTE_BANDS": "",
+        "NIGHT_LTE_ARFCNS": "",
+        "NIGHT_LTE_PCIS": "",
+        "NIGHT_NR_TYPE": 0,
+        "NIGHT_NR_BANDS": "",
+        "NIGHT_NR_ARFCNS": "",
+        "NIGHT_NR_SCS_TYPES": "",
+        "NIGHT_NR_PCIS": "",
+        "DAY_ENABLED": true,
+        "DAY_LTE_TYPE": 0,
+        "DAY_LTE_BANDS": "",
+        "DAY_LTE_ARFCNS": "",
+        "DAY_LTE_PCIS": "",
+        "DAY_NR_TYPE": 0,
+        "DAY_NR_BANDS": "",
+        "DAY_NR_ARFCNS": "",
+        "DAY_NR_SCS_TYPES": "",
+        "DAY_NR_PCIS": ""
+    },
+    "MQTT_CONTROL_CONFIG": {
+        "ENABLED": false,
+        "BROKER": "",
+        "TOPIC_PREFIX": "mt5700"
+    },
+    "MODULE_TIMING_CONFIG": {
+        "COMMAND_TIMEOUT_MS": 1000,
+        "POLL_INTERVAL_MS": 50,
+        "CFUN_OFF_SETTLE_SECS": 3,
+        "CFUN_ON_SETTLE_SECS": 8,
+        "INTER_COMMAND_GAP_SECS": 1,
+        "REGISTRATION_POLL_INTERVAL_SECS": 3
+    },
+    "COMMAND_SCHEDULER_CONFIG": {
+        "RATE_PER_SEC": 2.0,
+        "BURST": 3
+    },
+    "NETWORK_WATCHDOG_CONFIG": {
+        "CHECK_INTERVAL_SECS": 30,
+        "NO_SERVICE_TIMEOUT_SECS": 180,
+        "RECOVERY_GRACE_SECS": 30
+    },
+    "SERVICE_WATCHDOG_CONFIG": {
+        "INTERVAL_SECS": 30
     }
 }"#;
 
@@ -41,14 +130,47 @@ pub const DEFAULT_CONFIG_JSON: &str = r#"{
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
-    #[serde(rename = "AT_CONFIG")]
-    pub at_config: AtConfig,
+    /// 一台 OpenWrt 设备可挂多个调制解调器，按配置里的 ID 区分，例如双卡
+    /// 聚合场景下的 "default"/"sim2"。WebSocket 请求通过 ID 路由到对应
+    /// 的 `ATClient`。
+    #[serde(rename = "AT_CONFIGS")]
+    pub at_configs: HashMap<String, AtConfig>,
     #[serde(rename = "WEBSOCKET_CONFIG")]
     pub websocket_config: WsConfig,
+    /// WebSocket 认证失败的自动封禁节奏：见 [`BanConfig`]。
+    #[serde(rename = "WEBSOCKET_BAN_CONFIG")]
+    pub ban_config: BanConfig,
     #[serde(rename = "NOTIFICATION_CONFIG")]
     pub notification_config: NotificationConfig,
     #[serde(rename = "SCHEDULE_AIRPLANE_CONFIG")]
     pub auto_airplane: AutoAirPlane,
+    #[serde(rename = "SCHEDULE_CONFIG")]
+    pub schedule_config: ScheduleConfig,
+    #[serde(rename = "MQTT_CONTROL_CONFIG")]
+    pub mqtt_control: MqttMuxConfig,
+    #[serde(rename = "MODULE_TIMING_CONFIG")]
+    pub module_timing: ModuleTiming,
+    #[serde(rename = "COMMAND_SCHEDULER_CONFIG")]
+    pub scheduler_config: SchedulerConfig,
+    #[serde(rename = "NETWORK_WATCHDOG_CONFIG")]
+    pub watchdog_config: WatchdogConfig,
+    /// procd/systemd 监督续命的节奏：见 [`ServiceWatchdogConfig`]。
+    #[serde(rename = "SERVICE_WATCHDOG_CONFIG")]
+    pub service_watchdog_config: ServiceWatchdogConfig,
+}
+
+/// 约定的单模单元 ID：未显式配置多模前提下，所有历史行为都挂在这个 ID 上。
+pub const DEFAULT_MODEM_ID: &str = "default";
+
+impl Config {
+    /// 用于尚未升级到多模的代码路径（横幅打印、向导 `--check` 等）：返回
+    /// "default" 模块配置，若连它都不存在则退化为任意一个已配置的模块。
+    pub fn primary_at_config(&self) -> &AtConfig {
+        self.at_configs
+            .get(DEFAULT_MODEM_ID)
+            .or_else(|| self.at_configs.values().next())
+            .expect("AT_CONFIGS 至少要有一个模块")
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,6 +181,33 @@ pub struct AtConfig {
     pub network: NetworkConfig,
     #[serde(rename = "SERIAL")]
     pub serial: SerialConfig,
+    /// 经 WebSocket 中继访问 NAT 后面的模组：见 [`WebSocketConfig`]。只有
+    /// `TYPE = "WEBSOCKET"` 时才会用到。
+    #[serde(rename = "WS_RELAY", default)]
+    pub ws_relay: WebSocketConfig,
+    /// 模块型号提示（如 "MT5700M"），用来挑 `ModuleTiming::for_model` 的
+    /// 默认档位；留空则退化到通用档位。
+    #[serde(rename = "MODEL", default)]
+    pub model: String,
+    /// 主连接目标（上面的 `TYPE`/`NETWORK`/`SERIAL`）连续失败时按顺序依次
+    /// 顶上的后备端点，例如网络桥接掉线后退回串口。为空表示不启用故障
+    /// 转移，行为与之前完全一致。
+    #[serde(rename = "FALLBACK_ENDPOINTS", default)]
+    pub fallback_endpoints: Vec<AtEndpoint>,
+}
+
+/// 排在主连接目标之后的一个备用端点，字段含义与 `AtConfig` 的同名字段
+/// 一致，只是不需要单独的 `MODEL`。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AtEndpoint {
+    #[serde(rename = "TYPE")]
+    pub conn_type: String,
+    #[serde(rename = "NETWORK")]
+    pub network: NetworkConfig,
+    #[serde(rename = "SERIAL")]
+    pub serial: SerialConfig,
+    #[serde(rename = "WS_RELAY", default)]
+    pub ws_relay: WebSocketConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -69,6 +218,35 @@ pub struct NetworkConfig {
     pub port: u16,
     #[serde(rename = "TIMEOUT")]
     pub timeout: u64,
+    /// 可选的 TLS 终止：见 [`NetworkTlsConfig`]。`enabled = false`（缺省）
+    /// 时完全走明文 TCP，行为与之前一致。
+    #[serde(rename = "TLS", default)]
+    pub tls: NetworkTlsConfig,
+}
+
+/// AT-over-TCP 桥接的客户端 TLS 参数：许多 MT5700 部署把这条链路暴露在
+/// 不可信的局域网段上。`ENABLED` 为真时 `build_connection` 建
+/// `TlsNetworkATConn`，否则建明文 `NetworkATConn`；是否配了证书/私钥不
+/// 影响这个选择。
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NetworkTlsConfig {
+    #[serde(rename = "ENABLED", default)]
+    pub enabled: bool,
+    /// 自定义 CA 证书包路径（PEM），用于校验模组端的自签名证书；留空且未
+    /// 启用 `INSECURE_SKIP_VERIFY` 时建不出 TLS 连接（连接报错），不会改用
+    /// 系统信任的 CA。
+    #[serde(rename = "CA_PATH", default)]
+    pub ca_path: String,
+    /// 双向 TLS 的客户端证书/私钥路径（PEM），两者都留空表示不做客户端
+    /// 认证。
+    #[serde(rename = "CLIENT_CERT_PATH", default)]
+    pub client_cert_path: String,
+    #[serde(rename = "CLIENT_KEY_PATH", default)]
+    pub client_key_path: String,
+    /// 完全跳过证书校验，仅用于没有 CA 的自签名测试端点；启用后中间人
+    /// 攻击无法被发现，线上部署应该配 `CA_PATH` 而不是这个开关。
+    #[serde(rename = "INSECURE_SKIP_VERIFY", default)]
+    pub insecure_skip_verify: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -85,6 +263,24 @@ pub struct SerialConfig {
     pub feature: String,
 }
 
+/// AT-over-WebSocket 中继的客户端参数：NAT 后面的模组不用转发原始
+/// TCP/串口，只需要能访问这一个 `ws://`/`wss://` 中继端点。和服务端自己
+/// 监听用的 [`WsConfig`] 是两回事，这个是 `ATConnection` 作为客户端去连
+/// 别处时用的。
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WebSocketConfig {
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    /// 放进升级请求 `Authorization: Bearer <TOKEN>` 头里的认证令牌，留空
+    /// 表示中继端点不需要认证。
+    #[serde(rename = "TOKEN", default)]
+    pub token: String,
+    /// 客户端主动发送 ping 的间隔（秒），0 表示不主动发送，只被动应答对
+    /// 端发来的 ping。
+    #[serde(rename = "PING_INTERVAL_SECS", default)]
+    pub ping_interval_secs: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WsConfig {
     #[serde(rename = "IPV4")]
@@ -93,6 +289,82 @@ pub struct WsConfig {
     pub ipv6: WsEndpoint,
     #[serde(rename = "AUTH_KEY")]
     pub auth_key: String,
+    /// 可选的 WSS 终止：见 [`WsTlsConfig`]。`enabled = false`（缺省）时完全
+    /// 走明文 WebSocket，行为与之前一致。
+    #[serde(rename = "TLS", default)]
+    pub tls: WsTlsConfig,
+}
+
+/// 直接在本进程里终止 TLS，不依赖 OpenWrt 上的反向代理就能提供
+/// `wss://`。证书/私钥是 PEM 文件路径；`enabled = false` 时走明文连接，
+/// `enabled` 为真而证书/私钥加载失败（含留空）时启动报错退出。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WsTlsConfig {
+    #[serde(rename = "ENABLED", default = "default_ws_tls_enabled")]
+    pub enabled: bool,
+    #[serde(rename = "CERT_PATH", default)]
+    pub cert_path: String,
+    #[serde(rename = "KEY_PATH", default)]
+    pub key_path: String,
+}
+
+fn default_ws_tls_enabled() -> bool {
+    false
+}
+
+impl Default for WsTlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_ws_tls_enabled(),
+            cert_path: String::new(),
+            key_path: String::new(),
+        }
+    }
+}
+
+/// fail2ban 式的 WebSocket 认证暴力破解防护：滑动窗口内失败次数达到
+/// `threshold` 即封禁来源 IP，封禁时长从 `base_ban_secs` 起按失败轮次
+/// 翻倍、封顶 `max_ban_secs`。
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct BanConfig {
+    #[serde(rename = "ENABLED", default = "default_ban_enabled")]
+    pub enabled: bool,
+    #[serde(rename = "THRESHOLD", default = "default_ban_threshold")]
+    pub threshold: u32,
+    #[serde(rename = "WINDOW_SECS", default = "default_ban_window_secs")]
+    pub window_secs: u64,
+    #[serde(rename = "BASE_BAN_SECS", default = "default_ban_base_ban_secs")]
+    pub base_ban_secs: u64,
+    #[serde(rename = "MAX_BAN_SECS", default = "default_ban_max_ban_secs")]
+    pub max_ban_secs: u64,
+}
+
+fn default_ban_enabled() -> bool {
+    true
+}
+fn default_ban_threshold() -> u32 {
+    5
+}
+fn default_ban_window_secs() -> u64 {
+    300
+}
+fn default_ban_base_ban_secs() -> u64 {
+    60
+}
+fn default_ban_max_ban_secs() -> u64 {
+    3600
+}
+
+impl Default for BanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_ban_enabled(),
+            threshold: default_ban_threshold(),
+            window_secs: default_ban_window_secs(),
+            base_ban_secs: default_ban_base_ban_secs(),
+            max_ban_secs: default_ban_max_ban_secs(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -105,12 +377,39 @@ pub struct WsEndpoint {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NotificationConfig {
-    #[serde(rename = "WECHAT_WEBHOOK")]
-    pub wechat_webhook: String,
     #[serde(rename = "LOG_FILE")]
     pub log_file: String,
-    #[serde(rename = "NOTIFICATION_TYPES")]
-    pub notification_types: NotificationTypes,
+    /// 通知不再局限于单个企业微信 Webhook：这里是一组独立开关/限速的推送
+    /// 后端，同一事件（收到短信、存储满等）会依次投递给每一个启用的后端。
+    #[serde(rename = "BACKENDS")]
+    pub backends: Vec<NotifierBackendConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotifierBackendConfig {
+    /// "WECHAT_WORK" | "WEBHOOK" | "TELEGRAM" | "MQTT"
+    #[serde(rename = "KIND")]
+    pub kind: String,
+    #[serde(rename = "ENABLED")]
+    pub enabled: bool,
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    #[serde(rename = "BOT_TOKEN", default)]
+    pub bot_token: String,
+    #[serde(rename = "CHAT_ID", default)]
+    pub chat_id: String,
+    #[serde(rename = "BROKER", default)]
+    pub broker: String,
+    #[serde(rename = "TOPIC", default)]
+    pub topic: String,
+    #[serde(rename = "EVENTS")]
+    pub events: NotificationTypes,
+    #[serde(rename = "RATE_LIMIT_PER_MIN", default = "default_rate_limit_per_min")]
+    pub rate_limit_per_min: u32,
+}
+
+fn default_rate_limit_per_min() -> u32 {
+    20
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -123,6 +422,12 @@ pub struct NotificationTypes {
     pub memory_full: bool,
     #[serde(rename = "SIGNAL")]
     pub signal: bool,
+    #[serde(rename = "SCHEDULE_LOCK", default = "default_true")]
+    pub schedule_lock: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -130,81 +435,365 @@ pub struct AutoAirPlane {
     #[serde(rename = "ENABLED")]
     pub enabled: bool,
     #[serde(rename = "ACTION_TIME")]
-    pub action_time: String,
+    pub action_time: ActionTimeConfig,
+    /// 信号持续 0 格达到这么多分钟后额外触发一次 `restart_airplane_mode`，
+    /// 和时钟触发器并存；0 表示不启用这个触发方式。
+    #[serde(rename = "SIGNAL_LOSS_MINUTES", default)]
+    pub signal_loss_minutes: u32,
 }
 
-// ========== 从 UCI 加载配置 ==========
+/// `ACTION_TIME` 既可以是旧版的单一 `"HH:MM"` 字符串（每天生效），也可以是
+/// 一组 [`AirplaneTrigger`]（各自可选星期掩码），新旧写法都能直接反序列化。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ActionTimeConfig {
+    Single(String),
+    Multi(Vec<AirplaneTrigger>),
+}
 
-pub fn load_config_from_uci() -> Result<Config, Box<dyn Error>> {
-    println!("开始从 UCI 加载配置...");
+impl ActionTimeConfig {
+    /// 统一展开成触发器列表，供 `AutoAirPlaneMode` 消费。
+    pub fn triggers(&self) -> Vec<AirplaneTrigger> {
+        match self {
+            ActionTimeConfig::Single(time) => vec![AirplaneTrigger {
+                time: time.clone(),
+                weekdays: None,
+            }],
+            ActionTimeConfig::Multi(triggers) => triggers.clone(),
+        }
+    }
+}
 
-    // 执行 uci 命令
-    let output = Command::new("uci")
-        .args(&["show", "at-webserver"])
-        .output()?;
+/// 一个每日触发时刻，`weekdays` 是按位掩码的星期限定：bit0=周一 …
+/// bit6=周日，`None`（或 JSON 里缺省）表示每天都生效。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AirplaneTrigger {
+    #[serde(rename = "TIME")]
+    pub time: String,
+    #[serde(rename = "WEEKDAYS", default)]
+    pub weekdays: Option<u8>,
+}
 
-    if !output.status.success() {
-        println!("读取 UCI 配置失败，使用默认配置");
-        return serde_json::from_str(DEFAULT_CONFIG_JSON)
-            .map_err(|e| format!("解析默认配置失败: {}", e).into());
-    }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleConfig {
+    #[serde(rename = "ENABLED")]
+    pub enabled: bool,
+    #[serde(rename = "CHECK_INTERVAL")]
+    pub check_interval: u64,
+    #[serde(rename = "TIMEOUT")]
+    pub timeout: u64,
+    #[serde(rename = "UNLOCK_LTE")]
+    pub unlock_lte: bool,
+    #[serde(rename = "UNLOCK_NR")]
+    pub unlock_nr: bool,
+    #[serde(rename = "TOGGLE_AIRPLANE")]
+    pub toggle_airplane: bool,
+    #[serde(rename = "NIGHT_ENABLED")]
+    pub night_enabled: bool,
+    #[serde(rename = "NIGHT_START")]
+    pub night_start: String,
+    #[serde(rename = "NIGHT_END")]
+    pub night_end: String,
+    #[serde(rename = "NIGHT_LTE_TYPE")]
+    pub night_lte_type: u8,
+    #[serde(rename = "NIGHT_LTE_BANDS")]
+    pub night_lte_bands: String,
+    #[serde(rename = "NIGHT_LTE_ARFCNS")]
+    pub night_lte_arfcns: String,
+    #[serde(rename = "NIGHT_LTE_PCIS")]
+    pub night_lte_pcis: String,
+    #[serde(rename = "NIGHT_NR_TYPE")]
+    pub night_nr_type: u8,
+    #[serde(rename = "NIGHT_NR_BANDS")]
+    pub night_nr_bands: String,
+    #[serde(rename = "NIGHT_NR_ARFCNS")]
+    pub night_nr_arfcns: String,
+    #[serde(rename = "NIGHT_NR_SCS_TYPES")]
+    pub night_nr_scs_types: String,
+    #[serde(rename = "NIGHT_NR_PCIS")]
+    pub night_nr_pcis: String,
+    #[serde(rename = "DAY_ENABLED")]
+    pub day_enabled: bool,
+    #[serde(rename = "DAY_LTE_TYPE")]
+    pub day_lte_type: u8,
+    #[serde(rename = "DAY_LTE_BANDS")]
+    pub day_lte_bands: String,
+    #[serde(rename = "DAY_LTE_ARFCNS")]
+    pub day_lte_arfcns: String,
+    #[serde(rename = "DAY_LTE_PCIS")]
+    pub day_lte_pcis: String,
+    #[serde(rename = "DAY_NR_TYPE")]
+    pub day_nr_type: u8,
+    #[serde(rename = "DAY_NR_BANDS")]
+    pub day_nr_bands: String,
+    #[serde(rename = "DAY_NR_ARFCNS")]
+    pub day_nr_arfcns: String,
+    #[serde(rename = "DAY_NR_SCS_TYPES")]
+    pub day_nr_scs_types: String,
+    #[serde(rename = "DAY_NR_PCIS")]
+    pub day_nr_pcis: String,
+}
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let mut uci_data = HashMap::new();
+/// 不同模块/固件的收敛速度天差地别（尤其是 CFUN 切换后重新驻网），这里
+/// 把之前散落在 `send_command`、`AutoAirPlaneMode`、频率锁代码里的魔数
+/// 收进一处，可按 UCI 覆盖，也可以按 `AtConfig::model` 挑一套预置档位。
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ModuleTiming {
+    /// 单条命令等待终止码的总超时。
+    #[serde(rename = "COMMAND_TIMEOUT_MS", default = "default_command_timeout_ms")]
+    pub command_timeout_ms: u64,
+    /// 等待响应期间两次轮询之间的间隔。
+    #[serde(rename = "POLL_INTERVAL_MS", default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// 下发 `AT+CFUN=0` 后到继续下一步之间的等待，给模块留出进入飞行模式
+    /// 的时间。
+    #[serde(rename = "CFUN_OFF_SETTLE_SECS", default = "default_cfun_off_settle_secs")]
+    pub cfun_off_settle_secs: u64,
+    /// 下发 `AT+CFUN=1` 后到继续下一步之间的等待，给模块留出重新驻网的
+    /// 时间（通常比关闭飞行模式慢得多）。
+    #[serde(rename = "CFUN_ON_SETTLE_SECS", default = "default_cfun_on_settle_secs")]
+    pub cfun_on_settle_secs: u64,
+    /// 锁频下发之间、核实轮询之间的通用间隔。
+    #[serde(
+        rename = "INTER_COMMAND_GAP_SECS",
+        default = "default_inter_command_gap_secs"
+    )]
+    pub inter_command_gap_secs: u64,
+    /// 轮询驻网状态（`AT+CEREG?` 等）的间隔。
+    #[serde(
+        rename = "REGISTRATION_POLL_INTERVAL_SECS",
+        default = "default_registration_poll_interval_secs"
+    )]
+    pub registration_poll_interval_secs: u64,
+}
 
-    // 解析 UCI 输出
-    for line in output_str.trim().lines() {
-        if line.contains('=') {
-            let parts: Vec<&str> = line.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                let key = parts[0];
-                let value = parts[1].trim_matches(|c| c == '\'' || c == '"');
+fn default_command_timeout_ms() -> u64 {
+    1000
+}
+fn default_poll_interval_ms() -> u64 {
+    50
+}
+fn default_cfun_off_settle_secs() -> u64 {
+    2
+}
+fn default_cfun_on_settle_secs() -> u64 {
+    3
+}
+fn default_inter_command_gap_secs() -> u64 {
+    1
+}
+fn default_registration_poll_interval_secs() -> u64 {
+    2
+}
 
-                // 移除前缀 'at-webserver.config.'
-                if key.starts_with("at-webserver.config.") {
-                    let short_key = key.replace("at-webserver.config.", "");
-                    uci_data.insert(short_key, value.to_string());
-                }
+impl Default for ModuleTiming {
+    fn default() -> Self {
+        Self {
+            command_timeout_ms: default_command_timeout_ms(),
+            poll_interval_ms: default_poll_interval_ms(),
+            cfun_off_settle_secs: default_cfun_off_settle_secs(),
+            cfun_on_settle_secs: default_cfun_on_settle_secs(),
+            inter_command_gap_secs: default_inter_command_gap_secs(),
+            registration_poll_interval_secs: default_registration_poll_interval_secs(),
+        }
+    }
+}
+
+impl ModuleTiming {
+    /// 按模块型号提示挑一套已知更合适的档位；认不出的型号退化到通用默认值。
+    /// MT5700 系列切换 CFUN 后重新驻网明显更慢，需要更长的结算时间。
+    pub fn for_model(model: &str) -> Self {
+        if model.to_uppercase().contains("MT5700") {
+            Self {
+                cfun_off_settle_secs: 3,
+                cfun_on_settle_secs: 8,
+                registration_poll_interval_secs: 3,
+                ..Self::default()
             }
+        } else {
+            Self::default()
         }
     }
+}
 
-    // 从默认配置开始
-    println!("使用默认配置初始化...");
-    let mut config: Config = serde_json::from_str(DEFAULT_CONFIG_JSON)?;
+/// MQTT 控制面：把 `mt5700/cmd/#` 下的几个主题映射到具体 AT 操作，并把
+/// 链路状态/上报周期性发布到 `mt5700/status/*`、`mt5700/urc/*`。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MqttMuxConfig {
+    #[serde(rename = "ENABLED")]
+    pub enabled: bool,
+    #[serde(rename = "BROKER")]
+    pub broker: String,
+    #[serde(rename = "TOPIC_PREFIX", default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "mt5700".to_string()
+}
+
+/// 命令调度器的令牌桶参数：背景轮询（`Priority::Keepalive`）按
+/// `rate_per_sec` 持续获得令牌、最多攒到 `burst` 个，超发时排队等下一轮
+/// 刷新；交互/调度这两档优先级不受这个桶限制，始终先于背景轮询出队。
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct SchedulerConfig {
+    #[serde(rename = "RATE_PER_SEC", default = "default_scheduler_rate_per_sec")]
+    pub rate_per_sec: f64,
+    #[serde(rename = "BURST", default = "default_scheduler_burst")]
+    pub burst: u32,
+}
+
+fn default_scheduler_rate_per_sec() -> f64 {
+    2.0
+}
+fn default_scheduler_burst() -> u32 {
+    3
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            rate_per_sec: default_scheduler_rate_per_sec(),
+            burst: default_scheduler_burst(),
+        }
+    }
+}
+
+/// 无服务自愈看门狗的节奏参数：多久探测一次驻网状态、容忍多久没有服务、
+/// 每轮恢复尝试后给模块多长的反应时间再重新判断。
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct WatchdogConfig {
+    #[serde(rename = "CHECK_INTERVAL_SECS", default = "default_watchdog_check_interval_secs")]
+    pub check_interval_secs: u64,
+    #[serde(
+        rename = "NO_SERVICE_TIMEOUT_SECS",
+        default = "default_watchdog_no_service_timeout_secs"
+    )]
+    pub no_service_timeout_secs: u64,
+    #[serde(rename = "RECOVERY_GRACE_SECS", default = "default_watchdog_recovery_grace_secs")]
+    pub recovery_grace_secs: u64,
+}
+
+fn default_watchdog_check_interval_secs() -> u64 {
+    30
+}
+fn default_watchdog_no_service_timeout_secs() -> u64 {
+    180
+}
+fn default_watchdog_recovery_grace_secs() -> u64 {
+    30
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: default_watchdog_check_interval_secs(),
+            no_service_timeout_secs: default_watchdog_no_service_timeout_secs(),
+            recovery_grace_secs: default_watchdog_recovery_grace_secs(),
+        }
+    }
+}
+
+/// procd/systemd 的 sd-notify WATCHDOG= 续命间隔：应该比监督单元配置的
+/// `WatchdogSec`/procd `trigger` 更短，否则链路卡死之后还没来得及续命
+/// 就先被判定超时了。
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct ServiceWatchdogConfig {
+    #[serde(rename = "INTERVAL_SECS", default = "default_service_watchdog_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_service_watchdog_interval_secs() -> u64 {
+    30
+}
+
+impl Default for ServiceWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_service_watchdog_interval_secs(),
+        }
+    }
+}
+
+// ========== 从 UCI 加载配置 ==========
+
+/// 用给定前缀下的 UCI 键填充单个模块的连接配置。`prefix` 为空时读取
+/// "default" 模块沿用的原始扁平键（如 `connection_type`）；非空时读取
+/// `modem_<id>_*` 形式的附加模块键。
+fn load_at_config_from_uci(at_config: &mut AtConfig, uci_data: &HashMap<String, String>, prefix: &str) {
+    let key = |name: &str| format!("{}{}", prefix, name);
 
-    println!("开始从 UCI 加载配置...");
-    // 读取连接类型
     let conn_type = uci_data
-        .get("connection_type")
+        .get(&key("connection_type"))
         .map(|s| s.as_str())
-        .unwrap_or("NETWORK");
-    config.at_config.conn_type = conn_type.to_string();
-    println!("配置加载: 连接类型 = {}", conn_type);
+        .unwrap_or(&at_config.conn_type)
+        .to_string();
+    at_config.conn_type = conn_type.clone();
+
+    if let Some(model) = uci_data.get(&key("model")) {
+        at_config.model = model.clone();
+    }
 
-    // 读取网络配置
     if conn_type == "NETWORK" {
         let host = uci_data
-            .get("network_host")
+            .get(&key("network_host"))
             .map(|s| s.as_str())
             .unwrap_or("192.168.8.1");
         let port = uci_data
-            .get("network_port")
+            .get(&key("network_port"))
             .map(|s| s.parse().unwrap_or(20249))
             .unwrap_or(20249);
         let timeout = uci_data
-            .get("network_timeout")
+            .get(&key("network_timeout"))
             .map(|s| s.parse().unwrap_or(10))
             .unwrap_or(10);
 
-        config.at_config.network.host = host.to_string();
-        config.at_config.network.port = port;
-        config.at_config.network.timeout = timeout;
-        println!("配置加载: 网络连接 {}:{} (超时: {}秒)", host, port, timeout);
+        at_config.network.host = host.to_string();
+        at_config.network.port = port;
+        at_config.network.timeout = timeout;
+        println!(
+            "配置加载: {}网络连接 {}:{} (超时: {}秒)",
+            prefix, host, port, timeout
+        );
+
+        if let Some(v) = uci_data.get(&key("network_tls_enabled")) {
+            at_config.network.tls.enabled = v == "1";
+        }
+        if let Some(v) = uci_data.get(&key("network_tls_ca_path")) {
+            at_config.network.tls.ca_path = v.clone();
+        }
+        if let Some(v) = uci_data.get(&key("network_tls_client_cert_path")) {
+            at_config.network.tls.client_cert_path = v.clone();
+        }
+        if let Some(v) = uci_data.get(&key("network_tls_client_key_path")) {
+            at_config.network.tls.client_key_path = v.clone();
+        }
+        if let Some(v) = uci_data.get(&key("network_tls_insecure_skip_verify")) {
+            at_config.network.tls.insecure_skip_verify = v == "1";
+        }
+        if at_config.network.tls.enabled {
+            println!(
+                "配置加载: {}网络连接已启用 TLS (CA: {}, 跳过校验: {})",
+                prefix, at_config.network.tls.ca_path, at_config.network.tls.insecure_skip_verify
+            );
+        }
+    } else if conn_type == "WEBSOCKET" {
+        if let Some(v) = uci_data.get(&key("ws_relay_url")) {
+            at_config.ws_relay.url = v.clone();
+        }
+        if let Some(v) = uci_data.get(&key("ws_relay_token")) {
+            at_config.ws_relay.token = v.clone();
+        }
+        if let Some(v) = uci_data.get(&key("ws_relay_ping_interval_secs")) {
+            at_config.ws_relay.ping_interval_secs = v.parse().unwrap_or(30);
+        }
+        println!(
+            "配置加载: {}WebSocket 中继连接 {} (ping 间隔: {}秒)",
+            prefix, at_config.ws_relay.url, at_config.ws_relay.ping_interval_secs
+        );
     } else {
-        // 读取串口配置
         let mut port = uci_data
-            .get("serial_port")
+            .get(&key("serial_port"))
             .map(|s| s.as_str())
             .unwrap_or("/dev/ttyUSB0")
             .to_string();
@@ -212,43 +801,126 @@ pub fn load_config_from_uci() -> Result<Config, Box<dyn Error>> {
         // 如果选择了自定义路径，读取自定义值
         if port == "custom" {
             port = uci_data
-                .get("serial_port_custom")
+                .get(&key("serial_port_custom"))
                 .map(|s| s.as_str())
                 .unwrap_or("/dev/ttyUSB0")
                 .to_string();
         }
 
         let baudrate = uci_data
-            .get("serial_baudrate")
+            .get(&key("serial_baudrate"))
             .map(|s| s.parse().unwrap_or(115200))
             .unwrap_or(115200);
         let timeout = uci_data
-            .get("serial_timeout")
+            .get(&key("serial_timeout"))
             .map(|s| s.parse().unwrap_or(10))
             .unwrap_or(10);
 
-        config.at_config.serial.port = port.clone();
-        config.at_config.serial.baudrate = baudrate;
-        config.at_config.serial.timeout = timeout;
+        at_config.serial.port = port.clone();
+        at_config.serial.baudrate = baudrate;
+        at_config.serial.timeout = timeout;
 
-        // 读取串口方法和功能
         let method = uci_data
-            .get("serial_method")
+            .get(&key("serial_method"))
             .map(|s| s.as_str())
             .unwrap_or("TOM_MODEM");
         let feature = uci_data
-            .get("serial_feature")
+            .get(&key("serial_feature"))
             .map(|s| s.as_str())
             .unwrap_or("UBUS");
 
-        config.at_config.serial.method = method.to_string();
-        config.at_config.serial.feature = feature.to_string();
+        at_config.serial.method = method.to_string();
+        at_config.serial.feature = feature.to_string();
 
         println!(
-            "配置加载: 串口连接 {} @ {} bps (超时: {}秒)",
-            port, baudrate, timeout
+            "配置加载: {}串口连接 {} @ {} bps (超时: {}秒)",
+            prefix, port, baudrate, timeout
         );
-        println!("配置加载: 串口方法 = {}, 功能 = {}", method, feature);
+        println!("配置加载: {}串口方法 = {}, 功能 = {}", prefix, method, feature);
+    }
+
+    // 可选的故障转移后备端点：`<prefix>fallback_endpoint_ids`（逗号分隔）
+    // 声明一组 ID，每个 ID 复用同一套 connection_type/network_*/serial_*
+    // 键，只是换成 `<prefix>fallback_<id>_` 前缀，这样单个模块也能配出
+    // "网络优先、串口兜底" 这样的顺序。
+    if let Some(ids) = uci_data.get(&key("fallback_endpoint_ids")) {
+        let mut fallback_endpoints = Vec::new();
+        for id in ids.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let mut endpoint_config = at_config.clone();
+            load_at_config_from_uci(&mut endpoint_config, uci_data, &key(&format!("fallback_{}_", id)));
+            println!("配置加载: {}后备端点 \"{}\" 已加入故障转移列表", prefix, id);
+            fallback_endpoints.push(AtEndpoint {
+                conn_type: endpoint_config.conn_type,
+                network: endpoint_config.network,
+                serial: endpoint_config.serial,
+                ws_relay: endpoint_config.ws_relay,
+            });
+        }
+        at_config.fallback_endpoints = fallback_endpoints;
+    }
+}
+
+pub fn load_config_from_uci() -> Result<Config, Box<dyn Error>> {
+    println!("开始从 UCI 加载配置...");
+
+    // 执行 uci 命令
+    let output = Command::new("uci")
+        .args(&["show", "at-webserver"])
+        .output()?;
+
+    if !output.status.success() {
+        println!("读取 UCI 配置失败，使用默认配置");
+        return serde_json::from_str(DEFAULT_CONFIG_JSON)
+            .map_err(|e| format!("解析默认配置失败: {}", e).into());
+    }
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    let mut uci_data = HashMap::new();
+
+    // 解析 UCI 输出
+    for line in output_str.trim().lines() {
+        if line.contains('=') {
+            let parts: Vec<&str> = line.splitn(2, '=').collect();
+            if parts.len() == 2 {
+                let key = parts[0];
+                let value = parts[1].trim_matches(|c| c == '\'' || c == '"');
+
+                // 移除前缀 'at-webserver.config.'
+                if key.starts_with("at-webserver.config.") {
+                    let short_key = key.replace("at-webserver.config.", "");
+                    uci_data.insert(short_key, value.to_string());
+                }
+            }
+        }
+    }
+
+    // 从默认配置开始
+    println!("使用默认配置初始化...");
+    let mut config: Config = serde_json::from_str(DEFAULT_CONFIG_JSON)?;
+
+    println!("开始从 UCI 加载配置...");
+
+    // "default" 模块沿用原有的一组扁平键，保证单模部署的 UCI 配置不用改。
+    if !config.at_configs.contains_key(DEFAULT_MODEM_ID) {
+        let fallback = config.primary_at_config().clone();
+        config.at_configs.insert(DEFAULT_MODEM_ID.to_string(), fallback);
+    }
+    load_at_config_from_uci(
+        config.at_configs.get_mut(DEFAULT_MODEM_ID).unwrap(),
+        &uci_data,
+        "",
+    );
+
+    // 额外模块通过 `modem_ids`（逗号分隔）声明，每个模块读取 `modem_<id>_*`
+    // 前缀下的同名键。这样一台 OpenWrt 设备就能通过单一 WebSocket 端点
+    // 同时前端挂载多个 MT5700/5G 加密狗（例如双卡聚合）。
+    if let Some(ids) = uci_data.get("modem_ids") {
+        for id in ids.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let mut at_config = config.primary_at_config().clone();
+            load_at_config_from_uci(&mut at_config, &uci_data, &format!("modem_{}_", id));
+            println!("配置加载: 附加模块 \"{}\" = {}", id, at_config.conn_type);
+            config.at_configs.insert(id.to_string(), at_config);
+        }
     }
 
     // 读取 WebSocket 端口
@@ -259,8 +931,8 @@ pub fn load_config_from_uci() -> Result<Config, Box<dyn Error>> {
     config.websocket_config.ipv4.port = ws_port;
     config.websocket_config.ipv6.port = ws_port;
 
-    // 读取是否允许外网访问（仅用于打印提示）
-    let _allow_wan = uci_data
+    // 读取是否允许外网访问
+    let allow_wan = uci_data
         .get("websocket_allow_wan")
         .map(|s| s == "1")
         .unwrap_or(false);
@@ -276,50 +948,339 @@ pub fn load_config_from_uci() -> Result<Config, Box<dyn Error>> {
         .unwrap_or("");
     config.websocket_config.auth_key = auth_key.to_string();
 
-    // 读取通知配置
-    if let Some(wechat_webhook) = uci_data.get("wechat_webhook") {
-        config.notification_config.wechat_webhook = wechat_webhook.clone();
-        println!("配置加载: 企业微信推送已启用");
+    if allow_wan {
+        println!("配置加载: WebSocket 端口 = {} (允许外网访问)", ws_port);
+        println!("⚠ 外网访问已启用，请确保已配置防火墙规则保护");
+    } else {
+        println!("配置加载: WebSocket 端口 = {} (局域网访问)", ws_port);
+        println!("💡 如需限制访问，建议配置防火墙规则");
     }
 
-    if let Some(log_file) = uci_data.get("log_file") {
-        config.notification_config.log_file = log_file.clone();
-        println!("配置加载: 日志文件 = {}", log_file);
+    if !auth_key.is_empty() {
+        println!("配置加载: 连接密钥已设置 (长度: {})", auth_key.len());
+    } else {
+        println!("配置加载: 连接密钥未设置 (允许无密钥访问)");
     }
 
-    // 读取通知类型开关
-    if let Some(notify_sms) = uci_data.get("notify_sms") {
-        config.notification_config.notification_types.sms = notify_sms == "1";
+    // 读取 WSS 证书配置：留空/未启用时完全走明文 WebSocket
+    if let Some(v) = uci_data.get("websocket_tls_enabled") {
+        config.websocket_config.tls.enabled = v == "1";
+    }
+    if let Some(v) = uci_data.get("websocket_tls_cert_path") {
+        config.websocket_config.tls.cert_path = v.clone();
+    }
+    if let Some(v) = uci_data.get("websocket_tls_key_path") {
+        config.websocket_config.tls.key_path = v.clone();
+    }
+    if config.websocket_config.tls.enabled {
+        println!(
+            "配置加载: WSS 已启用 (证书: {}, 私钥: {})",
+            config.websocket_config.tls.cert_path, config.websocket_config.tls.key_path
+        );
+    }
+
+    // 读取 WebSocket 认证防暴力破解的封禁节奏
+    if let Some(v) = uci_data.get("websocket_ban_enabled") {
+        config.ban_config.enabled = v == "1";
+    }
+    if let Some(v) = uci_data.get("websocket_ban_threshold") {
+        config.ban_config.threshold = v.parse().unwrap_or(config.ban_config.threshold);
+    }
+    if let Some(v) = uci_data.get("websocket_ban_window_secs") {
+        config.ban_config.window_secs = v.parse().unwrap_or(config.ban_config.window_secs);
+    }
+    if let Some(v) = uci_data.get("websocket_ban_base_secs") {
+        config.ban_config.base_ban_secs = v.parse().unwrap_or(config.ban_config.base_ban_secs);
     }
-    if let Some(notify_call) = uci_data.get("notify_call") {
-        config.notification_config.notification_types.call = notify_call == "1";
+    if let Some(v) = uci_data.get("websocket_ban_max_secs") {
+        config.ban_config.max_ban_secs = v.parse().unwrap_or(config.ban_config.max_ban_secs);
     }
-    if let Some(notify_memory_full) = uci_data.get("notify_memory_full") {
-        config.notification_config.notification_types.memory_full = notify_memory_full == "1";
+    if config.ban_config.enabled {
+        println!(
+            "配置加载: WebSocket 认证防护已启用 (窗口 {} 秒内失败 {} 次封禁，起始 {} 秒、封顶 {} 秒)",
+            config.ban_config.window_secs,
+            config.ban_config.threshold,
+            config.ban_config.base_ban_secs,
+            config.ban_config.max_ban_secs
+        );
+    } else {
+        println!("配置加载: WebSocket 认证防护未启用");
     }
-    if let Some(notify_signal) = uci_data.get("notify_signal") {
-        config.notification_config.notification_types.signal = notify_signal == "1";
+
+    // 读取通知配置。历史上只有一路企业微信 webhook，这里沿用原有的扁平键，
+    // 落到 BACKENDS 里那个 "WECHAT_WORK" 条目上；其余后端（通用 webhook、
+    // Telegram、MQTT）没有对应的 UCI 键，只能通过默认 JSON 配置。
+    if let Some(wechat) = config
+        .notification_config
+        .backends
+        .iter_mut()
+        .find(|b| b.kind == "WECHAT_WORK")
+    {
+        if let Some(webhook) = uci_data.get("wechat_webhook") {
+            wechat.url = webhook.clone();
+            wechat.enabled = !webhook.is_empty();
+            println!("配置加载: 企业微信推送已启用");
+        }
+        if let Some(notify_sms) = uci_data.get("notify_sms") {
+            wechat.events.sms = notify_sms == "1";
+        }
+        if let Some(notify_call) = uci_data.get("notify_call") {
+            wechat.events.call = notify_call == "1";
+        }
+        if let Some(notify_memory_full) = uci_data.get("notify_memory_full") {
+            wechat.events.memory_full = notify_memory_full == "1";
+        }
+        if let Some(notify_signal) = uci_data.get("notify_signal") {
+            wechat.events.signal = notify_signal == "1";
+        }
+    }
+
+    if let Some(log_file) = uci_data.get("log_file") {
+        config.notification_config.log_file = log_file.clone();
+        println!("配置加载: 日志文件 = {}", log_file);
     }
 
     // 读取自动开关飞行模式
     if let Some(auto_airplane) = uci_data.get("schedule_auto_airplane_enable") {
         let enabled = auto_airplane == "1";
-        let action_time = uci_data
-            .get("schedule_airplane_time")
-            .map(|s: &String| s.as_str())
-            .unwrap_or("8:00")
-            .to_string();
+        config.auto_airplane.enabled = enabled;
 
+        // 新版：`schedule_airplane_times`（逗号分隔的 "HH:MM" 列表）可以一天
+        // 触发多次，配上可选的 `schedule_airplane_weekdays`（逗号分隔的
+        // 1-7，周一为 1）限定只在哪几天生效，对列表里的每条触发时刻都生
+        // 效。旧版单值键 `schedule_airplane_time` 仍然兼容，换算成唯一一
+        // 条触发时刻，同样受 `schedule_airplane_weekdays` 限定（未设置时
+        // 每天触发）。
+        let weekdays = uci_data.get("schedule_airplane_weekdays").map(|s| {
+            s.split(',')
+                .filter_map(|d| d.trim().parse::<u8>().ok())
+                .filter(|d| (1..=7).contains(d))
+                .fold(0u8, |mask, d| mask | (1 << (d - 1)))
+        });
+
+        if let Some(times) = uci_data.get("schedule_airplane_times") {
+            let triggers: Vec<AirplaneTrigger> = times
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|time| AirplaneTrigger {
+                    time: time.to_string(),
+                    weekdays,
+                })
+                .collect();
+            if !triggers.is_empty() {
+                println!(
+                    "配置加载: 自动开关飞行模式 = {} (触发时刻: {})",
+                    if enabled { "启用" } else { "禁用" },
+                    times
+                );
+                config.auto_airplane.action_time = ActionTimeConfig::Multi(triggers);
+            }
+        } else if let Some(action_time) = uci_data.get("schedule_airplane_time") {
+            println!(
+                "配置加载: 自动开关飞行模式 = {} (时间: {})",
+                if enabled { "启用" } else { "禁用" },
+                action_time
+            );
+            config.auto_airplane.action_time = ActionTimeConfig::Multi(vec![AirplaneTrigger {
+                time: action_time.clone(),
+                weekdays,
+            }]);
+        }
+
+        if let Some(v) = uci_data.get("schedule_airplane_signal_loss_minutes") {
+            config.auto_airplane.signal_loss_minutes =
+                v.parse().unwrap_or(config.auto_airplane.signal_loss_minutes);
+        }
+    }
+
+    // 读取定时锁频配置
+    if let Some(schedule_enabled) = uci_data.get("schedule_enabled") {
+        config.schedule_config.enabled = schedule_enabled == "1";
+    }
+
+    if let Some(check_interval) = uci_data.get("schedule_check_interval") {
+        config.schedule_config.check_interval = check_interval.parse().unwrap_or(60);
+    }
+
+    if let Some(schedule_timeout) = uci_data.get("schedule_timeout") {
+        config.schedule_config.timeout = schedule_timeout.parse().unwrap_or(180);
+    }
+
+    if let Some(unlock_lte) = uci_data.get("schedule_unlock_lte") {
+        config.schedule_config.unlock_lte = unlock_lte == "1";
+    }
+
+    if let Some(unlock_nr) = uci_data.get("schedule_unlock_nr") {
+        config.schedule_config.unlock_nr = unlock_nr == "1";
+    }
+
+    if let Some(toggle_airplane) = uci_data.get("schedule_toggle_airplane") {
+        config.schedule_config.toggle_airplane = toggle_airplane == "1";
+    }
+
+    // 夜间模式配置
+    if let Some(night_enabled) = uci_data.get("schedule_night_enabled") {
+        config.schedule_config.night_enabled = night_enabled == "1";
+    }
+
+    if let Some(night_start) = uci_data.get("schedule_night_start") {
+        config.schedule_config.night_start = night_start.clone();
+    }
+
+    if let Some(night_end) = uci_data.get("schedule_night_end") {
+        config.schedule_config.night_end = night_end.clone();
+    }
+
+    if let Some(night_lte_type) = uci_data.get("schedule_night_lte_type") {
+        config.schedule_config.night_lte_type = night_lte_type.parse().unwrap_or(0);
+    }
+
+    if let Some(night_lte_bands) = uci_data.get("schedule_night_lte_bands") {
+        config.schedule_config.night_lte_bands = night_lte_bands.clone();
+    }
+
+    if let Some(night_lte_arfcns) = uci_data.get("schedule_night_lte_arfcns") {
+        config.schedule_config.night_lte_arfcns = night_lte_arfcns.clone();
+    }
+
+    if let Some(night_lte_pcis) = uci_data.get("schedule_night_lte_pcis") {
+        config.schedule_config.night_lte_pcis = night_lte_pcis.clone();
+    }
+
+    if let Some(night_nr_type) = uci_data.get("schedule_night_nr_type") {
+        config.schedule_config.night_nr_type = night_nr_type.parse().unwrap_or(0);
+    }
+
+    if let Some(night_nr_bands) = uci_data.get("schedule_night_nr_bands") {
+        config.schedule_config.night_nr_bands = night_nr_bands.clone();
+    }
+
+    if let Some(night_nr_arfcns) = uci_data.get("schedule_night_nr_arfcns") {
+        config.schedule_config.night_nr_arfcns = night_nr_arfcns.clone();
+    }
+
+    if let Some(night_nr_scs_types) = uci_data.get("schedule_night_nr_scs_types") {
+        config.schedule_config.night_nr_scs_types = night_nr_scs_types.clone();
+    }
+
+    if let Some(night_nr_pcis) = uci_data.get("schedule_night_nr_pcis") {
+        config.schedule_config.night_nr_pcis = night_nr_pcis.clone();
+    }
+
+    // 日间模式配置
+    if let Some(day_enabled) = uci_data.get("schedule_day_enabled") {
+        config.schedule_config.day_enabled = day_enabled == "1";
+    }
+
+    if let Some(day_lte_type) = uci_data.get("schedule_day_lte_type") {
+        config.schedule_config.day_lte_type = day_lte_type.parse().unwrap_or(0);
+    }
+
+    if let Some(day_lte_bands) = uci_data.get("schedule_day_lte_bands") {
+        config.schedule_config.day_lte_bands = day_lte_bands.clone();
+    }
+
+    if let Some(day_lte_arfcns) = uci_data.get("schedule_day_lte_arfcns") {
+        config.schedule_config.day_lte_arfcns = day_lte_arfcns.clone();
+    }
+
+    if let Some(day_lte_pcis) = uci_data.get("schedule_day_lte_pcis") {
+        config.schedule_config.day_lte_pcis = day_lte_pcis.clone();
+    }
+
+    if let Some(day_nr_type) = uci_data.get("schedule_day_nr_type") {
+        config.schedule_config.day_nr_type = day_nr_type.parse().unwrap_or(0);
+    }
+
+    if let Some(day_nr_bands) = uci_data.get("schedule_day_nr_bands") {
+        config.schedule_config.day_nr_bands = day_nr_bands.clone();
+    }
+
+    if let Some(day_nr_arfcns) = uci_data.get("schedule_day_nr_arfcns") {
+        config.schedule_config.day_nr_arfcns = day_nr_arfcns.clone();
+    }
+
+    if let Some(day_nr_scs_types) = uci_data.get("schedule_day_nr_scs_types") {
+        config.schedule_config.day_nr_scs_types = day_nr_scs_types.clone();
+    }
+
+    if let Some(day_nr_pcis) = uci_data.get("schedule_day_nr_pcis") {
+        config.schedule_config.day_nr_pcis = day_nr_pcis.clone();
+    }
+
+    // 先按 "default" 模块的型号提示挑一套预置时序档位，再用显式 UCI 键
+    // （如果有）逐项覆盖，这样大多数部署只需要填对 MODEL 就行。
+    config.module_timing = ModuleTiming::for_model(&config.primary_at_config().model);
+
+    if let Some(v) = uci_data.get("module_timing_command_timeout_ms") {
+        config.module_timing.command_timeout_ms = v.parse().unwrap_or(config.module_timing.command_timeout_ms);
+    }
+    if let Some(v) = uci_data.get("module_timing_poll_interval_ms") {
+        config.module_timing.poll_interval_ms = v.parse().unwrap_or(config.module_timing.poll_interval_ms);
+    }
+    if let Some(v) = uci_data.get("module_timing_cfun_off_settle_secs") {
+        config.module_timing.cfun_off_settle_secs = v.parse().unwrap_or(config.module_timing.cfun_off_settle_secs);
+    }
+    if let Some(v) = uci_data.get("module_timing_cfun_on_settle_secs") {
+        config.module_timing.cfun_on_settle_secs = v.parse().unwrap_or(config.module_timing.cfun_on_settle_secs);
+    }
+    if let Some(v) = uci_data.get("module_timing_inter_command_gap_secs") {
+        config.module_timing.inter_command_gap_secs = v.parse().unwrap_or(config.module_timing.inter_command_gap_secs);
+    }
+    if let Some(v) = uci_data.get("module_timing_registration_poll_interval_secs") {
+        config.module_timing.registration_poll_interval_secs =
+            v.parse().unwrap_or(config.module_timing.registration_poll_interval_secs);
+    }
+
+    // 读取命令调度器的令牌桶参数
+    if let Some(v) = uci_data.get("scheduler_rate_per_sec") {
+        config.scheduler_config.rate_per_sec = v.parse().unwrap_or(config.scheduler_config.rate_per_sec);
+    }
+    if let Some(v) = uci_data.get("scheduler_burst") {
+        config.scheduler_config.burst = v.parse().unwrap_or(config.scheduler_config.burst);
+    }
+
+    // 读取无服务看门狗的节奏参数
+    if let Some(v) = uci_data.get("watchdog_check_interval_secs") {
+        config.watchdog_config.check_interval_secs =
+            v.parse().unwrap_or(config.watchdog_config.check_interval_secs);
+    }
+    if let Some(v) = uci_data.get("watchdog_no_service_timeout_secs") {
+        config.watchdog_config.no_service_timeout_secs =
+            v.parse().unwrap_or(config.watchdog_config.no_service_timeout_secs);
+    }
+    if let Some(v) = uci_data.get("watchdog_recovery_grace_secs") {
+        config.watchdog_config.recovery_grace_secs =
+            v.parse().unwrap_or(config.watchdog_config.recovery_grace_secs);
+    }
+
+    // 读取 procd/systemd 续命间隔
+    if let Some(v) = uci_data.get("service_watchdog_interval_secs") {
+        config.service_watchdog_config.interval_secs =
+            v.parse().unwrap_or(config.service_watchdog_config.interval_secs);
+    }
+
+    // 读取 MQTT 控制面配置
+    if let Some(mqtt_enabled) = uci_data.get("mqtt_control_enabled") {
+        config.mqtt_control.enabled = mqtt_enabled == "1";
+    }
+
+    if let Some(mqtt_broker) = uci_data.get("mqtt_control_broker") {
+        config.mqtt_control.broker = mqtt_broker.clone();
+    }
+
+    if let Some(mqtt_topic_prefix) = uci_data.get("mqtt_control_topic_prefix") {
+        config.mqtt_control.topic_prefix = mqtt_topic_prefix.clone();
+    }
+
+    if config.mqtt_control.enabled {
         println!(
-            "配置加载: 自动开关飞行模式 = {} (时间: {})",
-            if enabled { "启用" } else { "禁用" },
-            action_time
+            "配置加载: MQTT 控制面已启用 (broker: {}, 主题前缀: {})",
+            config.mqtt_control.broker, config.mqtt_control.topic_prefix
         );
-
-        config.auto_airplane.enabled = enabled;
-        config.auto_airplane.action_time = action_time;
     }
 
     println!("✓ UCI 配置加载完成");
     Ok(config)
-}
\ No newline at end of file
+}
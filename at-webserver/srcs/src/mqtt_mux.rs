@@ -0,0 +1,221 @@
+// MQTT 控制面：把 `{prefix}/cmd/#` 下的几个主题映射到具体 AT 操作，并把
+// 链路状态/上报周期性发布到 `{prefix}/status/*`、`{prefix}/urc/*`，这样
+// 家庭自动化系统（Home Assistant 等）也能直接驱动模块，不必只走本地网页。
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::interval;
+
+use crate::at::parser::urc_topic;
+use crate::at::scheduler::{CommandScheduler, Priority};
+use crate::at::ATClient;
+use crate::config::MqttMuxConfig;
+use crate::schedule::{build_lte_command, build_nr_command};
+
+/// `{prefix}/cmd/#` 下解析出来的一条具体指令。解析失败（主题不认识、
+/// payload 不合法）的一律拒绝，不会落到串口上。
+enum Topic {
+    Airplane,
+    LteLock,
+    NrLock,
+    Raw,
+}
+
+fn parse_topic(prefix: &str, topic: &str) -> Option<Topic> {
+    let rest = topic.strip_prefix(&format!("{}/cmd/", prefix))?;
+    match rest {
+        "airplane" => Some(Topic::Airplane),
+        "lte_lock" => Some(Topic::LteLock),
+        "nr_lock" => Some(Topic::NrLock),
+        "raw" => Some(Topic::Raw),
+        _ => None,
+    }
+}
+
+/// `lte_lock`/`nr_lock` 的 payload：`{"type":1,"bands":"3,41","arfcns":"...","pcis":"...","scs_types":"..."}`
+#[derive(serde::Deserialize)]
+struct FreqLockPayload {
+    #[serde(rename = "type")]
+    lock_type: u8,
+    #[serde(default)]
+    bands: String,
+    #[serde(default)]
+    arfcns: String,
+    #[serde(default)]
+    pcis: String,
+    #[serde(default)]
+    scs_types: String,
+}
+
+/// 把一条已解析的 `Topic` 连同其 payload 转换成要下发的 AT 命令；校验不
+/// 通过（payload 解不出来、raw 指令不是 AT 开头）返回 `None`，拒绝下发。
+fn build_command(topic: &Topic, payload: &str) -> Option<String> {
+    match topic {
+        Topic::Airplane => match payload.trim() {
+            "0" => Some("AT+CFUN=0\r\n".to_string()),
+            "1" => Some("AT+CFUN=1\r\n".to_string()),
+            _ => None,
+        },
+        Topic::LteLock => {
+            let p: FreqLockPayload = serde_json::from_str(payload).ok()?;
+            Some(build_lte_command(p.lock_type, &p.bands, &p.arfcns, &p.pcis))
+        }
+        Topic::NrLock => {
+            let p: FreqLockPayload = serde_json::from_str(payload).ok()?;
+            Some(build_nr_command(
+                p.lock_type,
+                &p.bands,
+                &p.arfcns,
+                &p.scs_types,
+                &p.pcis,
+            ))
+        }
+        Topic::Raw => {
+            let cmd = payload.trim();
+            if cmd.to_uppercase().starts_with("AT") {
+                Some(format!("{}\r\n", cmd))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// 起三个后台任务：一个订阅 `{prefix}/cmd/#` 并把解析出的命令经
+/// `scheduler` 以 `Interactive` 优先级下发；一个把 URC 转发到
+/// `{prefix}/urc/...`；一个周期性轮询状态（以 `Keepalive` 优先级下发，
+/// 被令牌桶限速，不与真正的命令抢排队位置），以 retained 消息发布到
+/// `{prefix}/status/*`。
+pub fn spawn(client: Arc<ATClient>, scheduler: Arc<CommandScheduler>, config: MqttMuxConfig) {
+    if !config.enabled {
+        return;
+    }
+
+    let (host, port) = config
+        .broker
+        .split_once(':')
+        .map(|(h, p)| (h.to_string(), p.parse().unwrap_or(1883)))
+        .unwrap_or((config.broker.clone(), 1883));
+
+    let prefix = config.topic_prefix.clone();
+    let cmd_filter = format!("{}/cmd/#", prefix);
+
+    let mut mqttoptions = rumqttc::MqttOptions::new("at-webserver-mux", host, port);
+    mqttoptions.set_keep_alive(Duration::from_secs(30));
+
+    let (mqtt_client, mut eventloop) = rumqttc::AsyncClient::new(mqttoptions, 64);
+
+    // 命令分发：订阅 cmd 主题树，解析成 `Topic` 后拼出 AT 命令下发。
+    let dispatch_client = mqtt_client.clone();
+    let dispatch_prefix = prefix.clone();
+    let dispatch_scheduler = scheduler.clone();
+    tokio::spawn(async move {
+        if let Err(e) = dispatch_client
+            .subscribe(&cmd_filter, rumqttc::QoS::AtLeastOnce)
+            .await
+        {
+            println!("[MQTT] 订阅 {} 失败: {}", cmd_filter, e);
+            return;
+        }
+
+        loop {
+            match eventloop.poll().await {
+                Ok(rumqttc::Event::Incoming(rumqttc::Packet::Publish(publish))) => {
+                    let payload = String::from_utf8_lossy(&publish.payload).to_string();
+                    match parse_topic(&dispatch_prefix, &publish.topic) {
+                        Some(topic) => match build_command(&topic, &payload) {
+                            Some(command) => {
+                                println!("[MQTT] {} -> {}", publish.topic, command.trim());
+                                if let Err(e) = dispatch_scheduler
+                                    .submit_str(Priority::Interactive, command)
+                                    .await
+                                {
+                                    println!("[MQTT] 命令下发失败: {}", e);
+                                }
+                            }
+                            None => println!(
+                                "[MQTT] 主题 {} 的 payload 非法，已拒绝: {:?}",
+                                publish.topic, payload
+                            ),
+                        },
+                        None => println!("[MQTT] 未知命令主题: {}", publish.topic),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    println!("[MQTT] 连接异常: {}，2 秒后重试", e);
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+            }
+        }
+    });
+
+    // URC 转发：把 `urc_tx` 上的每一条上报原样转发到 `{prefix}/urc/...`。
+    let urc_client = mqtt_client.clone();
+    let urc_prefix = prefix.clone();
+    let mut urc_rx = client.urc_tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match urc_rx.recv().await {
+                Ok(line) => {
+                    let topic = format!("{}/urc/{}", urc_prefix, urc_topic(&line));
+                    let _ = urc_client
+                        .publish(&topic, rumqttc::QoS::AtMostOnce, false, line)
+                        .await;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // 状态轮询：定期查询 CREG/信号/已锁频段，以 retained 消息发布，订阅方
+    // 一上线就能拿到最新状态而不必等下一次变化。
+    let status_client = mqtt_client;
+    let status_prefix = prefix;
+    let status_scheduler = scheduler;
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(30));
+        loop {
+            ticker.tick().await;
+            publish_status(&status_client, &status_prefix, &status_scheduler, "AT+CREG?\r\n", "creg").await;
+            publish_status(&status_client, &status_prefix, &status_scheduler, "AT+CSQ\r\n", "signal").await;
+            publish_status(
+                &status_client,
+                &status_prefix,
+                &status_scheduler,
+                "AT^LTEFREQLOCK?\r\n",
+                "locked_bands",
+            )
+            .await;
+
+            let topic = format!("{}/status/module_state", status_prefix);
+            let _ = status_client
+                .publish(
+                    &topic,
+                    rumqttc::QoS::AtLeastOnce,
+                    true,
+                    format!("{:?}", client.module_state()),
+                )
+                .await;
+        }
+    });
+}
+
+async fn publish_status(
+    mqtt_client: &rumqttc::AsyncClient,
+    prefix: &str,
+    scheduler: &Arc<CommandScheduler>,
+    command: &str,
+    suffix: &str,
+) {
+    if let Ok(response) = scheduler
+        .submit_str(Priority::Keepalive, command.to_string())
+        .await
+    {
+        let topic = format!("{}/status/{}", prefix, suffix);
+        let _ = mqtt_client
+            .publish(&topic, rumqttc::QoS::AtLeastOnce, true, response)
+            .await;
+    }
+}
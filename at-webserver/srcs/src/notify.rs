@@ -0,0 +1,302 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::config::{NotificationConfig, NotifierBackendConfig};
+
+/// 需要对外推送的事件。对应 `NotificationTypes` 里的五类开关。
+#[derive(Debug, Clone)]
+pub enum NotifyEvent {
+    Sms { from: String, body: String },
+    Call { from: String },
+    MemoryFull,
+    Signal { rssi: i32 },
+    ScheduleLock {
+        mode: String,
+        success: bool,
+        detail: String,
+    },
+}
+
+impl NotifyEvent {
+    fn is_enabled(&self, events: &crate::config::NotificationTypes) -> bool {
+        match self {
+            NotifyEvent::Sms { .. } => events.sms,
+            NotifyEvent::Call { .. } => events.call,
+            NotifyEvent::MemoryFull => events.memory_full,
+            NotifyEvent::Signal { .. } => events.signal,
+            NotifyEvent::ScheduleLock { .. } => events.schedule_lock,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            NotifyEvent::Sms { .. } => "收到短信",
+            NotifyEvent::Call { .. } => "来电提醒",
+            NotifyEvent::MemoryFull => "存储空间已满",
+            NotifyEvent::Signal { .. } => "信号变化",
+            NotifyEvent::ScheduleLock { success, .. } => {
+                if *success {
+                    "定时锁频成功"
+                } else {
+                    "定时锁频超时"
+                }
+            }
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            NotifyEvent::Sms { from, body } => format!("来自 {} 的短信: {}", from, body),
+            NotifyEvent::Call { from } => format!("来自 {} 的来电", from),
+            NotifyEvent::MemoryFull => "模块存储空间已满，短信可能无法继续接收".to_string(),
+            NotifyEvent::Signal { rssi } => format!("当前信号强度 RSSI = {}", rssi),
+            NotifyEvent::ScheduleLock { detail, .. } => detail.clone(),
+        }
+    }
+}
+
+/// 单个推送后端。每种后端各自决定怎么把一条 `NotifyEvent` 发出去；
+/// 重试、限速由 `NotifierHub` 统一处理，后端只管“发一次”。
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+    async fn send(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// 企业微信群机器人 webhook。
+pub struct WechatWorkNotifier {
+    pub webhook: String,
+}
+
+#[async_trait]
+impl Notifier for WechatWorkNotifier {
+    fn name(&self) -> &str {
+        "企业微信"
+    }
+
+    async fn send(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let body = serde_json::json!({
+            "msgtype": "text",
+            "text": { "content": format!("[{}] {}", event.title(), event.text()) }
+        });
+        let client = reqwest::Client::new();
+        let resp = client.post(&self.webhook).json(&body).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("企业微信 webhook 返回状态码 {}", resp.status()).into());
+        }
+        Ok(())
+    }
+}
+
+/// 不限定格式的通用 JSON webhook，body 里带事件类型与文本，供任意下游系统接入。
+pub struct GenericWebhookNotifier {
+    pub url: String,
+}
+
+#[async_trait]
+impl Notifier for GenericWebhookNotifier {
+    fn name(&self) -> &str {
+        "通用Webhook"
+    }
+
+    async fn send(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let body = serde_json::json!({
+            "title": event.title(),
+            "message": event.text(),
+        });
+        let client = reqwest::Client::new();
+        let resp = client.post(&self.url).json(&body).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("webhook 返回状态码 {}", resp.status()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Telegram Bot API。
+pub struct TelegramNotifier {
+    pub bot_token: String,
+    pub chat_id: String,
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "Telegram"
+    }
+
+    async fn send(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let body = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": format!("[{}] {}", event.title(), event.text()),
+        });
+        let client = reqwest::Client::new();
+        let resp = client.post(&url).json(&body).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("Telegram API 返回状态码 {}", resp.status()).into());
+        }
+        Ok(())
+    }
+}
+
+/// MQTT 发布，供家庭自动化系统（Home Assistant 等）订阅。
+pub struct MqttNotifier {
+    pub broker: String,
+    pub topic: String,
+}
+
+#[async_trait]
+impl Notifier for MqttNotifier {
+    fn name(&self) -> &str {
+        "MQTT"
+    }
+
+    async fn send(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (host, port) = self
+            .broker
+            .split_once(':')
+            .map(|(h, p)| (h.to_string(), p.parse().unwrap_or(1883)))
+            .unwrap_or((self.broker.clone(), 1883));
+
+        let mut mqttoptions = rumqttc::MqttOptions::new("at-webserver", host, port);
+        mqttoptions.set_keep_alive(Duration::from_secs(5));
+
+        let (client, mut eventloop) = rumqttc::AsyncClient::new(mqttoptions, 10);
+        let payload = serde_json::json!({ "title": event.title(), "message": event.text() }).to_string();
+        client
+            .publish(&self.topic, rumqttc::QoS::AtLeastOnce, false, payload)
+            .await?;
+
+        // 把本次发布的确认事件排空，避免常驻连接；一次性发布用完即走。
+        for _ in 0..4 {
+            match tokio::time::timeout(Duration::from_secs(2), eventloop.poll()).await {
+                Ok(Ok(_)) => continue,
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 滑动窗口令牌桶，避免某个后端被突发的 URC（典型如频繁的信号变化）刷爆。
+struct RateLimiter {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn per_minute(limit: u32) -> Self {
+        let capacity = limit.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+struct BackendSlot {
+    notifier: Box<dyn Notifier>,
+    events: crate::config::NotificationTypes,
+    limiter: RateLimiter,
+}
+
+/// 同一事件依次投递给每一个启用的后端；每个后端各自限速、各自重试，互不影响。
+pub struct NotifierHub {
+    backends: Vec<BackendSlot>,
+}
+
+const MAX_RETRIES: u32 = 3;
+const RETRY_BACKOFF: Duration = Duration::from_millis(300);
+
+impl NotifierHub {
+    pub fn from_config(config: &NotificationConfig) -> Self {
+        let backends = config
+            .backends
+            .iter()
+            .filter(|b| b.enabled)
+            .filter_map(|b| build_notifier(b).map(|notifier| (b, notifier)))
+            .map(|(b, notifier)| BackendSlot {
+                notifier,
+                events: b.events.clone(),
+                limiter: RateLimiter::per_minute(b.rate_limit_per_min),
+            })
+            .collect();
+        Self { backends }
+    }
+
+    pub async fn notify(&mut self, event: NotifyEvent) {
+        for slot in &mut self.backends {
+            if !event.is_enabled(&slot.events) {
+                continue;
+            }
+            if !slot.limiter.try_acquire() {
+                println!("[Notify] {} 已被限速，丢弃本次事件", slot.notifier.name());
+                continue;
+            }
+
+            let mut attempt = 0;
+            loop {
+                match slot.notifier.send(&event).await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        attempt += 1;
+                        if attempt >= MAX_RETRIES {
+                            println!(
+                                "[Notify] {} 推送失败，共尝试 {} 次: {}",
+                                slot.notifier.name(),
+                                attempt,
+                                e
+                            );
+                            break;
+                        }
+                        sleep(RETRY_BACKOFF * attempt).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn build_notifier(config: &NotifierBackendConfig) -> Option<Box<dyn Notifier>> {
+    match config.kind.as_str() {
+        "WECHAT_WORK" => Some(Box::new(WechatWorkNotifier {
+            webhook: config.url.clone(),
+        })),
+        "WEBHOOK" => Some(Box::new(GenericWebhookNotifier {
+            url: config.url.clone(),
+        })),
+        "TELEGRAM" => Some(Box::new(TelegramNotifier {
+            bot_token: config.bot_token.clone(),
+            chat_id: config.chat_id.clone(),
+        })),
+        "MQTT" => Some(Box::new(MqttNotifier {
+            broker: config.broker.clone(),
+            topic: config.topic.clone(),
+        })),
+        other => {
+            println!("[Notify] 未知的通知后端类型: {}", other);
+            None
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// procd/systemd 式的 sd-notify 集成：按其 READY/WATCHDOG 协议往
+// `NOTIFY_SOCKET` 指向的 Unix 数据报套接字发一行文本，没必要为这几个
+// 字节引入专门的客户端库。`NOTIFY_SOCKET` 不存在（没有被监督）时下面两
+// 个函数都原地退化为空操作。
+
+use std::env;
+use std::os::unix::net::UnixDatagram;
+
+fn notify(message: &str) {
+    let socket_path = match env::var("NOTIFY_SOCKET") {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+
+    let socket = match UnixDatagram::unbound() {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+
+    if let Err(e) = socket.send_to(message.as_bytes(), &socket_path) {
+        println!("[Readiness] 发送 sd-notify 消息失败: {}", e);
+    }
+}
+
+/// 监听器成功绑定、服务真正可用之后调用一次，告诉 procd/systemd 不用再
+/// 按 TimeoutStartSec 等下去了。
+pub fn notify_ready() {
+    notify("READY=1");
+}
+
+/// 周期性续命。只应该在调用方已经确认 AT 链路仍然存活时调用，这样链路
+/// 卡死时监督者会按 WatchdogSec 超时重启整个进程，而不是以为任务还在
+/// 跑、心跳没断就当作存活。
+pub fn notify_watchdog() {
+    notify("WATCHDOG=1");
+}
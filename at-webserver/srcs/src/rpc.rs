@@ -0,0 +1,155 @@
+// 命名 RPC 方法注册表：把 WebSocket 上 `{method, params, id}` 形式的请求
+// 分发给按名字注册的高阶接口（锁频、解锁、查信号、重启飞行模式……），
+// 内部各自拼出具体的 AT 命令。这样前端只依赖方法名和参数形状，不必再
+// 手写 `AT^NRFREQLOCK=...` 之类随固件变化的 AT 语法。
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::at::scheduler::{CommandScheduler, Priority};
+use crate::at::ATClient;
+use crate::schedule::{build_lte_command, build_nr_command, LockConfig};
+
+/// 一次方法调用携带的上下文：具体方法要用哪个模块的客户端/调度器，由
+/// WebSocket 层按请求里的 `modem` 字段解析好再传进来。
+pub struct RpcContext {
+    pub client: Arc<ATClient>,
+    pub scheduler: Arc<CommandScheduler>,
+}
+
+pub type RpcResult = Result<Value, String>;
+type RpcFuture = Pin<Box<dyn Future<Output = RpcResult> + Send>>;
+type RpcHandler = Arc<dyn Fn(RpcContext, Value) -> RpcFuture + Send + Sync>;
+
+/// 启动时注册好的一组命名方法，之后按方法名只读查表分发，不再变化。
+pub struct RpcRegistry {
+    methods: HashMap<&'static str, RpcHandler>,
+}
+
+impl RpcRegistry {
+    /// 分发一次调用；方法名不存在时返回 `Err`，由调用方包装成标准错误响应。
+    pub async fn dispatch(&self, method: &str, ctx: RpcContext, params: Value) -> RpcResult {
+        match self.methods.get(method) {
+            Some(handler) => handler(ctx, params).await,
+            None => Err(format!("未知方法: {}", method)),
+        }
+    }
+}
+
+fn handler<F, Fut>(f: F) -> RpcHandler
+where
+    F: Fn(RpcContext, Value) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = RpcResult> + Send + 'static,
+{
+    Arc::new(move |ctx, params| Box::pin(f(ctx, params)))
+}
+
+/// 建好内置的方法表。新增高阶方法时只需在这里追加一条 `insert`。
+pub fn build_registry() -> RpcRegistry {
+    let mut methods: HashMap<&'static str, RpcHandler> = HashMap::new();
+
+    methods.insert("lock_frequency", handler(lock_frequency));
+    methods.insert("unlock", handler(unlock));
+    methods.insert("get_signal", handler(get_signal));
+    methods.insert("reboot_airplane", handler(reboot_airplane));
+
+    RpcRegistry { methods }
+}
+
+/// `params` 直接反序列化成 `LockConfig`（与定时锁频用的是同一个类型），
+/// 缺省字段按 `LockConfig` 的 `#[serde(default)]` 落到 "不锁" 的档位。
+async fn lock_frequency(ctx: RpcContext, params: Value) -> RpcResult {
+    let lock: LockConfig = serde_json::from_value(params).map_err(|e| format!("参数解析失败: {}", e))?;
+
+    let lte_response = ctx
+        .scheduler
+        .submit_str(
+            Priority::Interactive,
+            build_lte_command(lock.lte_type, &lock.lte_bands, &lock.lte_arfcns, &lock.lte_pcis),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let nr_response = ctx
+        .scheduler
+        .submit_str(
+            Priority::Interactive,
+            build_nr_command(
+                lock.nr_type,
+                &lock.nr_bands,
+                &lock.nr_arfcns,
+                &lock.nr_scs_types,
+                &lock.nr_pcis,
+            ),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(json!({ "lte_response": lte_response, "nr_response": nr_response }))
+}
+
+/// `params`: `{"lte": bool, "nr": bool}`，缺省都解锁。
+async fn unlock(ctx: RpcContext, params: Value) -> RpcResult {
+    let unlock_lte = params.get("lte").and_then(Value::as_bool).unwrap_or(true);
+    let unlock_nr = params.get("nr").and_then(Value::as_bool).unwrap_or(true);
+
+    let mut result = json!({});
+    if unlock_lte {
+        let response = ctx
+            .scheduler
+            .submit_str(Priority::Interactive, build_lte_command(0, "", "", ""))
+            .await
+            .map_err(|e| e.to_string())?;
+        result["lte_response"] = json!(response);
+    }
+    if unlock_nr {
+        let response = ctx
+            .scheduler
+            .submit_str(Priority::Interactive, build_nr_command(0, "", "", "", ""))
+            .await
+            .map_err(|e| e.to_string())?;
+        result["nr_response"] = json!(response);
+    }
+
+    Ok(result)
+}
+
+/// 查询信号强度：下发 `AT+CSQ`，从 `+CSQ: <rssi>,<ber>` 里取出 RSSI。
+async fn get_signal(ctx: RpcContext, _params: Value) -> RpcResult {
+    let response = ctx
+        .scheduler
+        .submit_str(Priority::Interactive, "AT+CSQ\r\n".to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let rssi = response
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("+CSQ:"))
+        .and_then(|rest| rest.trim().split(',').next())
+        .and_then(|v| v.trim().parse::<i32>().ok());
+
+    Ok(json!({ "rssi": rssi, "raw": response }))
+}
+
+/// 手动触发一次飞行模式重启：CFUN=0 等结算、CFUN=1 等结算，复用
+/// `ATClient::timing` 里的结算时长，与定时锁频、自动飞行模式是同一套参数。
+async fn reboot_airplane(ctx: RpcContext, _params: Value) -> RpcResult {
+    ctx.scheduler
+        .submit_str(Priority::Interactive, "AT+CFUN=0\r\n".to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    sleep(Duration::from_secs(ctx.client.timing.cfun_off_settle_secs)).await;
+
+    ctx.scheduler
+        .submit_str(Priority::Interactive, "AT+CFUN=1\r\n".to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    sleep(Duration::from_secs(ctx.client.timing.cfun_on_settle_secs)).await;
+
+    Ok(json!({ "rebooted": true }))
+}
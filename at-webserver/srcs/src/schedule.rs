@@ -0,0 +1,783 @@
+// 定时锁频功能：按 `Asia/Shanghai` 时区的夜间/日间时间窗切换 LTE/NR 锁频
+// 参数，锁定后在超时内反复重新查询加以核对，并把结果推送给 `NotifierHub`。
+
+use chrono::Utc;
+use chrono_tz::Asia::Shanghai;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::at::scheduler::{CommandScheduler, Priority};
+use crate::at::ATClient;
+use crate::config::{Config, ModuleTiming};
+use crate::notify::{NotifierHub, NotifyEvent};
+
+/// 某一时段（夜间/日间）生效的锁频参数；字段全部 `#[serde(default)]`，
+/// 这样 `rpc::lock_frequency` 收到只设置了 LTE 或只设置了 NR 的请求时，
+/// 另一边自动落到类型 0（不锁）而不是报错。
+#[derive(Deserialize)]
+pub struct LockConfig {
+    #[serde(default)]
+    pub lte_type: u8,
+    #[serde(default)]
+    pub lte_bands: String,
+    #[serde(default)]
+    pub lte_arfcns: String,
+    #[serde(default)]
+    pub lte_pcis: String,
+    #[serde(default)]
+    pub nr_type: u8,
+    #[serde(default)]
+    pub nr_bands: String,
+    #[serde(default)]
+    pub nr_arfcns: String,
+    #[serde(default)]
+    pub nr_scs_types: String,
+    #[serde(default)]
+    pub nr_pcis: String,
+}
+
+impl LockConfig {
+    fn unlocked() -> Self {
+        Self {
+            lte_type: 0,
+            lte_bands: String::new(),
+            lte_arfcns: String::new(),
+            lte_pcis: String::new(),
+            nr_type: 0,
+            nr_bands: String::new(),
+            nr_arfcns: String::new(),
+            nr_scs_types: String::new(),
+            nr_pcis: String::new(),
+        }
+    }
+}
+
+/// 定时锁频功能
+pub struct ScheduleFrequencyLock {
+    client: Arc<ATClient>,
+    scheduler: Arc<CommandScheduler>,
+    notifier_hub: Arc<Mutex<NotifierHub>>,
+    timing: ModuleTiming,
+    enabled: bool,
+    check_interval: u64,
+    timeout: u64,
+    unlock_lte: bool,
+    unlock_nr: bool,
+    toggle_airplane: bool,
+    night_enabled: bool,
+    night_start: String,
+    night_end: String,
+    night_lte_type: u8,
+    night_lte_bands: String,
+    night_lte_arfcns: String,
+    night_lte_pcis: String,
+    night_nr_type: u8,
+    night_nr_bands: String,
+    night_nr_arfcns: String,
+    night_nr_scs_types: String,
+    night_nr_pcis: String,
+    day_enabled: bool,
+    day_lte_type: u8,
+    day_lte_bands: String,
+    day_lte_arfcns: String,
+    day_lte_pcis: String,
+    day_nr_type: u8,
+    day_nr_bands: String,
+    day_nr_arfcns: String,
+    day_nr_scs_types: String,
+    day_nr_pcis: String,
+
+    is_switching: bool,
+    switch_count: u32,
+    current_mode: Option<String>, // Some("night") 或 Some("day")
+}
+
+impl ScheduleFrequencyLock {
+    pub fn new(
+        client: Arc<ATClient>,
+        scheduler: Arc<CommandScheduler>,
+        config: Arc<Config>,
+        notifier_hub: Arc<Mutex<NotifierHub>>,
+    ) -> Self {
+        let schedule = &config.schedule_config;
+
+        let lock = Self {
+            client,
+            scheduler,
+            notifier_hub,
+            timing: config.module_timing,
+            enabled: schedule.enabled,
+            check_interval: schedule.check_interval,
+            timeout: schedule.timeout,
+            unlock_lte: schedule.unlock_lte,
+            unlock_nr: schedule.unlock_nr,
+            toggle_airplane: schedule.toggle_airplane,
+            night_enabled: schedule.night_enabled,
+            night_start: schedule.night_start.clone(),
+            night_end: schedule.night_end.clone(),
+            night_lte_type: schedule.night_lte_type,
+            night_lte_bands: schedule.night_lte_bands.clone(),
+            night_lte_arfcns: schedule.night_lte_arfcns.clone(),
+            night_lte_pcis: schedule.night_lte_pcis.clone(),
+            night_nr_type: schedule.night_nr_type,
+            night_nr_bands: schedule.night_nr_bands.clone(),
+            night_nr_arfcns: schedule.night_nr_arfcns.clone(),
+            night_nr_scs_types: schedule.night_nr_scs_types.clone(),
+            night_nr_pcis: schedule.night_nr_pcis.clone(),
+            day_enabled: schedule.day_enabled,
+            day_lte_type: schedule.day_lte_type,
+            day_lte_bands: schedule.day_lte_bands.clone(),
+            day_lte_arfcns: schedule.day_lte_arfcns.clone(),
+            day_lte_pcis: schedule.day_lte_pcis.clone(),
+            day_nr_type: schedule.day_nr_type,
+            day_nr_bands: schedule.day_nr_bands.clone(),
+            day_nr_arfcns: schedule.day_nr_arfcns.clone(),
+            day_nr_scs_types: schedule.day_nr_scs_types.clone(),
+            day_nr_pcis: schedule.day_nr_pcis.clone(),
+
+            is_switching: false,
+            switch_count: 0,
+            current_mode: None,
+        };
+
+        if lock.enabled {
+            println!("{}", "=".repeat(60));
+            println!("定时锁频功能已启用");
+            println!("  检测间隔: {} 秒", lock.check_interval);
+            println!("  锁频核实超时: {} 秒", lock.timeout);
+            println!(
+                "  夜间模式: {} ({}-{})",
+                if lock.night_enabled { "启用" } else { "禁用" },
+                lock.night_start,
+                lock.night_end
+            );
+            println!(
+                "  日间模式: {}",
+                if lock.day_enabled { "启用" } else { "禁用" }
+            );
+            println!(
+                "  解锁LTE: {}, 解锁NR: {}, 切飞行模式: {}",
+                if lock.unlock_lte { "是" } else { "否" },
+                if lock.unlock_nr { "是" } else { "否" },
+                if lock.toggle_airplane { "是" } else { "否" }
+            );
+            println!("{}", "=".repeat(60));
+        }
+
+        lock
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 判断当前是否落在夜间窗口内。时间字符串解析失败时返回 `None`，表示
+    /// 无法判断——调用方应保持当前状态不变，而不是误判成日间并强行切换。
+    fn is_night_time(&self) -> Option<bool> {
+        let now = Utc::now().with_timezone(&Shanghai);
+        let current_time = now.time();
+
+        let start_time = match chrono::NaiveTime::parse_from_str(&self.night_start, "%H:%M") {
+            Ok(t) => t,
+            Err(e) => {
+                println!("解析夜间开始时间 {:?} 失败: {}", self.night_start, e);
+                return None;
+            }
+        };
+        let end_time = match chrono::NaiveTime::parse_from_str(&self.night_end, "%H:%M") {
+            Ok(t) => t,
+            Err(e) => {
+                println!("解析夜间结束时间 {:?} 失败: {}", self.night_end, e);
+                return None;
+            }
+        };
+
+        Some(if start_time > end_time {
+            // 跨天窗口，例如 22:00-06:00
+            current_time >= start_time || current_time < end_time
+        } else {
+            current_time >= start_time && current_time < end_time
+        })
+    }
+
+    /// 根据当前是否夜间，结合 `night_enabled`/`day_enabled` 得到目标模式；
+    /// `Some(None)` 表示当前时段明确不需要锁频，`None` 表示时间窗口解析
+    /// 失败、无法判断——二者在 `monitor_loop` 里的处理方式不同。
+    fn get_current_mode(&self) -> Option<Option<String>> {
+        let is_night = self.is_night_time()?;
+
+        Some(if is_night {
+            if self.night_enabled {
+                Some("night".to_string())
+            } else {
+                None
+            }
+        } else if self.day_enabled {
+            Some("day".to_string())
+        } else {
+            None
+        })
+    }
+
+    fn get_lock_config_for_mode(&self, mode: &str) -> LockConfig {
+        if mode == "night" {
+            LockConfig {
+                lte_type: self.night_lte_type,
+                lte_bands: self.night_lte_bands.clone(),
+                lte_arfcns: self.night_lte_arfcns.clone(),
+                lte_pcis: self.night_lte_pcis.clone(),
+                nr_type: self.night_nr_type,
+                nr_bands: self.night_nr_bands.clone(),
+                nr_arfcns: self.night_nr_arfcns.clone(),
+                nr_scs_types: self.night_nr_scs_types.clone(),
+                nr_pcis: self.night_nr_pcis.clone(),
+            }
+        } else if mode == "day" {
+            LockConfig {
+                lte_type: self.day_lte_type,
+                lte_bands: self.day_lte_bands.clone(),
+                lte_arfcns: self.day_lte_arfcns.clone(),
+                lte_pcis: self.day_lte_pcis.clone(),
+                nr_type: self.day_nr_type,
+                nr_bands: self.day_nr_bands.clone(),
+                nr_arfcns: self.day_nr_arfcns.clone(),
+                nr_scs_types: self.day_nr_scs_types.clone(),
+                nr_pcis: self.day_nr_pcis.clone(),
+            }
+        } else {
+            LockConfig::unlocked()
+        }
+    }
+
+    /// 核实锁频是否生效：重新查询 `AT^LTEFREQLOCK?` / `AT^NRFREQLOCK?`，看返回
+    /// 是否包含刚下发的频段列表。在 `self.timeout` 秒内按
+    /// `timing.registration_poll_interval_secs` 轮询。
+    async fn verify_lock_applied(&self, config: &LockConfig) -> bool {
+        let lte_bands = config.lte_bands.trim();
+        let nr_bands = config.nr_bands.trim();
+        let check_lte = config.lte_type > 0 && !lte_bands.is_empty();
+        let check_nr = config.nr_type > 0 && !nr_bands.is_empty();
+
+        if !check_lte && !check_nr {
+            // 解锁场景没有具体频段可比对，只要下发命令成功就认为已生效。
+            return true;
+        }
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(self.timeout.max(1));
+
+        loop {
+            let mut lte_ok = !check_lte;
+            let mut nr_ok = !check_nr;
+
+            if check_lte {
+                if let Ok(response) = self
+                    .scheduler
+                    .submit_str(Priority::Scheduler, "AT^LTEFREQLOCK?\r\n".to_string())
+                    .await
+                {
+                    lte_ok = response.contains(lte_bands);
+                }
+            }
+
+            if check_nr {
+                if let Ok(response) = self
+                    .scheduler
+                    .submit_str(Priority::Scheduler, "AT^NRFREQLOCK?\r\n".to_string())
+                    .await
+                {
+                    nr_ok = response.contains(nr_bands);
+                }
+            }
+
+            if lte_ok && nr_ok {
+                return true;
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+
+            sleep(Duration::from_secs(
+                self.timing.registration_poll_interval_secs.max(1),
+            ))
+            .await;
+        }
+    }
+
+    async fn set_frequency_lock(&mut self, config: LockConfig, mode: &str) {
+        if self.is_switching {
+            return;
+        }
+
+        self.is_switching = true;
+        self.switch_count += 1;
+
+        println!("{}", "=".repeat(60));
+        println!(
+            "🔄 切换到{}模式锁频设置 (第 {} 次)",
+            mode, self.switch_count
+        );
+        println!("{}", "=".repeat(60));
+
+        let mut operations = Vec::new();
+
+        // 1. 进入飞行模式
+        if self.toggle_airplane {
+            println!("步骤 1: 进入飞行模式...");
+            match self
+                .scheduler
+                .submit_str(Priority::Scheduler, "AT+CFUN=0\r\n".to_string())
+                .await
+            {
+                Ok(_) => {
+                    println!("✓ 进入飞行模式");
+                    operations.push("切飞行模式".to_string());
+                    sleep(Duration::from_secs(self.timing.cfun_off_settle_secs)).await;
+                }
+                Err(e) => println!("✗ 进入飞行模式失败: {}", e),
+            }
+        }
+
+        // 2. 设置 LTE 锁频
+        let lte_type = config.lte_type;
+        if lte_type > 0 {
+            let lte_bands = config.lte_bands.trim();
+            if !lte_bands.is_empty() {
+                let command = build_lte_command(
+                    lte_type,
+                    lte_bands,
+                    &config.lte_arfcns,
+                    &config.lte_pcis,
+                );
+                println!("步骤 2: 设置 LTE 锁频 (类型: {})...", lte_type);
+                println!("  命令: {}", command.trim());
+
+                match self.scheduler.submit_str(Priority::Scheduler, command).await {
+                    Ok(_) => {
+                        println!("✓ LTE 锁频命令已下发");
+                        operations.push(format!("LTE锁频(类型{})", lte_type));
+                    }
+                    Err(e) => println!("✗ LTE 锁频下发失败: {}", e),
+                }
+                sleep(Duration::from_secs(self.timing.inter_command_gap_secs)).await;
+            }
+        } else if self.unlock_lte {
+            // 解锁 LTE
+            println!("步骤 2: 解锁 LTE...");
+            match self
+                .scheduler
+                .submit_str(Priority::Scheduler, "AT^LTEFREQLOCK=0\r\n".to_string())
+                .await
+            {
+                Ok(_) => {
+                    println!("✓ LTE 解锁命令已下发");
+                    operations.push("LTE解锁".to_string());
+                }
+                Err(e) => println!("✗ LTE 解锁失败: {}", e),
+            }
+            sleep(Duration::from_secs(self.timing.inter_command_gap_secs)).await;
+        }
+
+        // 3. 设置 NR 锁频
+        let nr_type = config.nr_type;
+        if nr_type > 0 {
+            let nr_bands = config.nr_bands.trim();
+            if !nr_bands.is_empty() {
+                let command = build_nr_command(
+                    nr_type,
+                    nr_bands,
+                    &config.nr_arfcns,
+                    &config.nr_scs_types,
+                    &config.nr_pcis,
+                );
+                println!("步骤 3: 设置 NR 锁频 (类型: {})...", nr_type);
+                println!("  命令: {}", command.trim());
+
+                match self.scheduler.submit_str(Priority::Scheduler, command).await {
+                    Ok(_) => {
+                        println!("✓ NR 锁频命令已下发");
+                        operations.push(format!("NR锁频(类型{})", nr_type));
+                    }
+                    Err(e) => println!("✗ NR 锁频下发失败: {}", e),
+                }
+                sleep(Duration::from_secs(self.timing.inter_command_gap_secs)).await;
+            }
+        } else if self.unlock_nr {
+            // 解锁 NR
+            println!("步骤 3: 解锁 NR...");
+            match self
+                .scheduler
+                .submit_str(Priority::Scheduler, "AT^NRFREQLOCK=0\r\n".to_string())
+                .await
+            {
+                Ok(_) => {
+                    println!("✓ NR 解锁命令已下发");
+                    operations.push("NR解锁".to_string());
+                }
+                Err(e) => println!("✗ NR 解锁失败: {}", e),
+            }
+            sleep(Duration::from_secs(self.timing.inter_command_gap_secs)).await;
+        }
+
+        // 4. 退出飞行模式使配置生效
+        if self.toggle_airplane {
+            println!("步骤 4: 退出飞行模式使配置生效...");
+            match self
+                .scheduler
+                .submit_str(Priority::Scheduler, "AT+CFUN=1\r\n".to_string())
+                .await
+            {
+                Ok(_) => println!("✓ 退出飞行模式"),
+                Err(e) => println!("✗ 退出飞行模式失败: {}", e),
+            }
+            sleep(Duration::from_secs(self.timing.cfun_on_settle_secs)).await;
+        }
+
+        // 5. 核实锁频是否真正生效
+        let verified = self.verify_lock_applied(&config).await;
+
+        let ops_text = if operations.is_empty() {
+            "未执行任何操作".to_string()
+        } else {
+            operations.join("、")
+        };
+
+        println!("{}", "=".repeat(60));
+        if verified {
+            println!("✓ 定时锁频切换完成并核实生效");
+        } else {
+            println!("✗ 定时锁频切换超时：重新查询未确认到预期的频段参数");
+        }
+        println!("  模式: {}模式", mode);
+        println!("  执行操作: {}", ops_text);
+        println!("  切换次数: 第 {} 次", self.switch_count);
+        println!("{}", "=".repeat(60));
+
+        let detail = format!("{}模式锁频（{}）：{}", mode, ops_text, if verified { "已核实生效" } else { "核实超时，参数可能未生效" });
+        let event = NotifyEvent::ScheduleLock {
+            mode: mode.to_string(),
+            success: verified,
+            detail,
+        };
+        let _ = self.client.urc_tx.send(format!(
+            "^SCHEDULELOCK: {},{}",
+            mode,
+            if verified { 1 } else { 0 }
+        ));
+        self.notifier_hub.lock().await.notify(event).await;
+
+        self.is_switching = false;
+    }
+
+    pub async fn monitor_loop(mut self) {
+        if !self.enabled {
+            println!("定时锁频功能已禁用");
+            return;
+        }
+
+        println!("启动定时锁频监控...");
+
+        loop {
+            match self.get_current_mode() {
+                None => {
+                    // 时间窗口解析失败，无法判断昼夜，保持上一次的锁频状态不变。
+                    println!("获取当前模式失败，本次检测跳过");
+                }
+                Some(target_mode) => {
+                    if let Some(ref target_mode_str) = target_mode {
+                        if Some(target_mode_str) != self.current_mode.as_ref() {
+                            let config = self.get_lock_config_for_mode(target_mode_str);
+                            println!(
+                                "检测到模式切换: {:?} -> {}",
+                                self.current_mode, target_mode_str
+                            );
+                            self.set_frequency_lock(config, target_mode_str).await;
+                            self.current_mode = target_mode.clone();
+                        }
+                    } else if self.current_mode.is_some() {
+                        // 当前时段不需要锁频，如果之前有锁频则解锁
+                        println!("当前时段不需要锁频，解锁所有频段");
+                        self.set_frequency_lock(LockConfig::unlocked(), "解锁").await;
+                        self.current_mode = None;
+                    }
+                }
+            }
+
+            sleep(Duration::from_secs(self.check_interval.max(1))).await;
+        }
+    }
+}
+
+/// 拼出 `AT^LTEFREQLOCK` 命令。抽成自由函数而非 `ScheduleFrequencyLock` 的
+/// 方法，好让 `mqtt_mux` 之类不关心昼夜调度状态的调用方也能直接复用。
+pub(crate) fn build_lte_command(lock_type: u8, bands: &str, arfcns: &str, pcis: &str) -> String {
+    if lock_type == 0 {
+        return "AT^LTEFREQLOCK=0\r\n".to_string();
+    }
+
+    let band_list: Vec<&str> = bands
+        .split(',')
+        .map(|b| b.trim())
+        .filter(|b| !b.is_empty())
+        .collect();
+
+    if lock_type == 3 {
+        // 频段锁定
+        if band_list.is_empty() {
+            return "AT^LTEFREQLOCK=0\r\n".to_string();
+        }
+        return format!(
+            "AT^LTEFREQLOCK=3,0,{},\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(",")
+        );
+    } else if lock_type == 1 {
+        // 频点锁定
+        let arfcn_list: Vec<&str> = arfcns
+            .split(',')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect();
+
+        if band_list.is_empty() || arfcn_list.is_empty() || band_list.len() != arfcn_list.len()
+        {
+            println!("LTE 频点锁定：频段和频点数量不匹配，解锁");
+            return "AT^LTEFREQLOCK=0\r\n".to_string();
+        }
+
+        return format!(
+            "AT^LTEFREQLOCK=1,0,{},\"{}\",\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(","),
+            arfcn_list.join(",")
+        );
+    } else if lock_type == 2 {
+        // 小区锁定
+        let arfcn_list: Vec<&str> = arfcns
+            .split(',')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect();
+        let pci_list: Vec<&str> = pcis
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        if band_list.is_empty()
+            || arfcn_list.is_empty()
+            || pci_list.is_empty()
+            || band_list.len() != arfcn_list.len()
+            || arfcn_list.len() != pci_list.len()
+        {
+            println!("LTE 小区锁定：频段、频点、PCI 数量不匹配，解锁");
+            return "AT^LTEFREQLOCK=0\r\n".to_string();
+        }
+
+        return format!(
+            "AT^LTEFREQLOCK=2,0,{},\"{}\",\"{}\",\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(","),
+            arfcn_list.join(","),
+            pci_list.join(",")
+        );
+    } else {
+        return "AT^LTEFREQLOCK=0\r\n".to_string();
+    }
+}
+
+/// 拼出 `AT^NRFREQLOCK` 命令，逻辑与 `build_lte_command` 对应；锁定类型
+/// 1/2 下若调用方未显式给出 SCS，退化调用 `auto_detect_scs_types` 兜底。
+pub(crate) fn build_nr_command(
+    lock_type: u8,
+    bands: &str,
+    arfcns: &str,
+    scs_types: &str,
+    pcis: &str,
+) -> String {
+    if lock_type == 0 {
+        return "AT^NRFREQLOCK=0\r\n".to_string();
+    }
+
+    let band_list: Vec<&str> = bands
+        .split(',')
+        .map(|b| b.trim())
+        .filter(|b| !b.is_empty())
+        .collect();
+
+    if lock_type == 3 {
+        // 频段锁定
+        if band_list.is_empty() {
+            return "AT^NRFREQLOCK=0\r\n".to_string();
+        }
+        return format!(
+            "AT^NRFREQLOCK=3,0,{},\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(",")
+        );
+    } else if lock_type == 1 {
+        // 频点锁定
+        let arfcn_list: Vec<&str> = arfcns
+            .split(',')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect();
+        let scs_list: Vec<String> = scs_types
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if band_list.is_empty() || arfcn_list.is_empty() || band_list.len() != arfcn_list.len()
+        {
+            println!("NR 频点锁定：频段和频点数量不匹配，解锁");
+            return "AT^NRFREQLOCK=0\r\n".to_string();
+        }
+
+        let final_scs_list = if scs_list.is_empty() || scs_list.len() != band_list.len() {
+            auto_detect_scs_types(&band_list, &arfcn_list)
+        } else {
+            scs_list
+        };
+
+        if final_scs_list.len() != band_list.len() {
+            println!("NR 频点锁定：SCS 类型数量不匹配，解锁");
+            return "AT^NRFREQLOCK=0\r\n".to_string();
+        }
+
+        return format!(
+            "AT^NRFREQLOCK=1,0,{},\"{}\",\"{}\",\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(","),
+            arfcn_list.join(","),
+            final_scs_list.join(",")
+        );
+    } else if lock_type == 2 {
+        // 小区锁定
+        let arfcn_list: Vec<&str> = arfcns
+            .split(',')
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .collect();
+        let scs_list: Vec<String> = scs_types
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let pci_list: Vec<&str> = pcis
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        if band_list.is_empty()
+            || arfcn_list.is_empty()
+            || pci_list.is_empty()
+            || band_list.len() != arfcn_list.len()
+            || arfcn_list.len() != pci_list.len()
+        {
+            println!("NR 小区锁定：频段、频点、PCI 数量不匹配，解锁");
+            return "AT^NRFREQLOCK=0\r\n".to_string();
+        }
+
+        let final_scs_list = if scs_list.is_empty() || scs_list.len() != band_list.len() {
+            auto_detect_scs_types(&band_list, &arfcn_list)
+        } else {
+            scs_list
+        };
+
+        if final_scs_list.len() != band_list.len() {
+            println!("NR 小区锁定：SCS 类型数量不匹配，解锁");
+            return "AT^NRFREQLOCK=0\r\n".to_string();
+        }
+
+        return format!(
+            "AT^NRFREQLOCK=2,0,{},\"{}\",\"{}\",\"{}\",\"{}\"\r\n",
+            band_list.len(),
+            band_list.join(","),
+            arfcn_list.join(","),
+            final_scs_list.join(","),
+            pci_list.join(",")
+        );
+    } else {
+        return "AT^NRFREQLOCK=0\r\n".to_string();
+    }
+}
+
+/// 依据 NR-ARFCN 换算出的参考频点挑 SCS（子载波间隔）类型，供调用方在
+/// UCI 未显式配置 `scs_types` 时兜底。频点解析失败（非数字或超出换算表
+/// 范围）才退化到按频段号猜的旧表。
+pub(crate) fn auto_detect_scs_types(bands: &[&str], arfcns: &[&str]) -> Vec<String> {
+    let mut scs_list = Vec::new();
+
+    for i in 0..bands.len().min(arfcns.len()) {
+        let band = bands[i];
+        let scs = arfcns[i]
+            .parse::<i64>()
+            .ok()
+            .and_then(nr_arfcn_to_freq_mhz)
+            .map(|freq_mhz| scs_from_frequency(freq_mhz).to_string())
+            .unwrap_or_else(|| scs_from_band_table(band));
+
+        scs_list.push(scs);
+    }
+
+    scs_list
+}
+
+/// NR-ARFCN（`N_REF`）换算参考频点（MHz），按 3GPP TS 38.104 表
+/// 5.4.2.1-1 的分段公式：
+/// `F_REF = F_REF_Offs + (ΔF_Global / 1000) * (N_REF − N_REF_Offs)`。
+/// 三段参数按 `N_REF` 落在哪个区间取（分别对应 0–3000MHz、3000–24250MHz、
+/// 24250–100000MHz 三个频率范围），超出表定义范围返回 `None`。
+fn nr_arfcn_to_freq_mhz(arfcn: i64) -> Option<f64> {
+    if arfcn < 0 {
+        return None;
+    }
+
+    let (delta_f_global_khz, f_ref_offs_mhz, n_ref_offs) = if arfcn < 600_000 {
+        (5.0, 0.0, 0)
+    } else if arfcn < 2_016_667 {
+        (15.0, 3000.0, 600_000)
+    } else if arfcn <= 3_279_165 {
+        (60.0, 24250.08, 2_016_667)
+    } else {
+        return None;
+    };
+
+    Some(f_ref_offs_mhz + (delta_f_global_khz / 1000.0) * (arfcn - n_ref_offs) as f64)
+}
+
+/// 按参考频点挑 SCS 档位（`AT^NRFREQLOCK` 用的是 "0"~"3" 这几个索引）：
+/// FR2（≥24250MHz）用 120kHz（"3"）；2.5GHz 以下的 FR1 低频 FDD 频段用
+/// 15kHz（"0"）；FR1 其余频点（2.5GHz 及以上，含 n41/n77/n78/n79 等 TDD
+/// 频段）用 30kHz（"1"）。
+fn scs_from_frequency(freq_mhz: f64) -> &'static str {
+    if freq_mhz >= 24250.0 {
+        "3"
+    } else if (2500.0..=4200.0).contains(&freq_mhz) {
+        "1"
+    } else if freq_mhz < 2500.0 {
+        "0"
+    } else {
+        "1"
+    }
+}
+
+/// 频点解析失败时的兜底：按频段号粗略猜 SCS，沿用换算支持之前的老表。
+fn scs_from_band_table(band: &str) -> String {
+    let scs = if let Ok(band_num) = band.parse::<i32>() {
+        match band_num {
+            78 | 79 | 258 | 260 => "1", // n78, n79, n258, n260 通常使用 30kHz SCS
+            41 | 77 => "1",             // n41, n77 通常使用 30kHz SCS
+            28 | 71 => "0",             // n28, n71 通常使用 15kHz SCS
+            _ => "1",                   // 默认使用 30kHz SCS
+        }
+    } else {
+        "1" // 默认使用 30kHz SCS
+    };
+    scs.to_string()
+}
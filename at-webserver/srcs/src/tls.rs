@@ -0,0 +1,163 @@
+// 可选的 WSS 终止：启用时用 `tokio-rustls` 在 TCP accept 之后、
+// WebSocket 升级之前插一次 TLS 握手，这样 OpenWrt 上不需要额外的反向代
+// 理也能提供 `wss://`。未启用时完全不走这条路径。
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use crate::config::{NetworkTlsConfig, WsTlsConfig};
+
+/// 按配置建一个 `TlsAcceptor`；`enabled = false` 时返回 `None`，调用方据此
+/// 决定走明文还是 TLS 分支。
+pub fn build_acceptor(config: &WsTlsConfig) -> Result<Option<TlsAcceptor>, Box<dyn Error>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+
+    let tls_config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+
+    Ok(Some(TlsAcceptor::from(Arc::new(tls_config))))
+}
+
+fn load_certs(path: &str) -> io::Result<Vec<rustls::pki_types::CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::certs(&mut reader).collect()
+}
+
+fn load_private_key(path: &str) -> io::Result<rustls::pki_types::PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "未找到私钥"))
+}
+
+/// 按 `NetworkTlsConfig` 建一个给 `TlsNetworkATConn::connect()` 用的
+/// `TlsConnector`：配了 `ca_path` 就用它校验模组证书；`ca_path` 留空且
+/// 未启用 `insecure_skip_verify` 时返回错误，不加载系统信任的 CA。配了客
+/// 户端证书/私钥就带上做双向 TLS；`insecure_skip_verify` 跳过校验，仅供
+/// 没有 CA 的自签名测试端点使用。
+pub fn build_network_connector(config: &NetworkTlsConfig) -> Result<TlsConnector, Box<dyn Error + Send + Sync>> {
+    let verifier_stage = if config.insecure_skip_verify {
+        rustls::ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(InsecureServerVerifier))
+    } else if !config.ca_path.is_empty() {
+        let mut root_store = rustls::RootCertStore::empty();
+        for cert in load_certs(&config.ca_path)? {
+            root_store.add(cert)?;
+        }
+        rustls::ClientConfig::builder().with_root_certificates(root_store)
+    } else {
+        return Err("网络连接启用了 TLS 但既未配置 CA_PATH 也未启用 INSECURE_SKIP_VERIFY".into());
+    };
+
+    let tls_config = if !config.client_cert_path.is_empty() {
+        let certs = load_certs(&config.client_cert_path)?;
+        let key = load_private_key(&config.client_key_path)?;
+        verifier_stage.with_client_auth_cert(certs, key)?
+    } else {
+        verifier_stage.with_no_client_auth()
+    };
+
+    Ok(TlsConnector::from(Arc::new(tls_config)))
+}
+
+/// 跳过证书链/主机名校验的 verifier，只给 `insecure_skip_verify` 用：自签
+/// 名的测试端点没有可核实的 CA，但仍然想走 TLS 加密链路而不是明文。放弃
+/// 了中间人防护，线上部署应该配 `CA_PATH` 而不是这个开关。
+#[derive(Debug)]
+struct InsecureServerVerifier;
+
+impl rustls::client::danger::ServerCertVerifier for InsecureServerVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &rustls::pki_types::CertificateDer<'_>,
+        _dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        Ok(rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        vec![
+            rustls::SignatureScheme::RSA_PKCS1_SHA256,
+            rustls::SignatureScheme::RSA_PKCS1_SHA384,
+            rustls::SignatureScheme::RSA_PKCS1_SHA512,
+            rustls::SignatureScheme::ECDSA_NISTP256_SHA256,
+            rustls::SignatureScheme::ECDSA_NISTP384_SHA384,
+            rustls::SignatureScheme::RSA_PSS_SHA256,
+            rustls::SignatureScheme::RSA_PSS_SHA384,
+            rustls::SignatureScheme::RSA_PSS_SHA512,
+            rustls::SignatureScheme::ED25519,
+        ]
+    }
+}
+
+/// 统一明文/TLS 两种连接类型，好让 `websocket::handle_connection` 不必关
+/// 心上层是不是套了一层 TLS，两种连接都实现同一套 `AsyncRead`/`AsyncWrite`。
+pub enum MaybeTlsStream {
+    Plain(TcpStream),
+    Tls(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for MaybeTlsStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MaybeTlsStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            MaybeTlsStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            MaybeTlsStream::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,23 +1,98 @@
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::TcpStream;
+use tokio::sync::mpsc;
 use tokio::time::timeout;
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 
-use crate::at::ATClient;
+use crate::at::parser::urc_topic;
+use crate::at::{ModemEvent, ModemRegistry, Priority};
+use crate::ban::BanGuard;
+use crate::rpc::{RpcContext, RpcRegistry};
+use crate::tls::MaybeTlsStream;
+
+/// 一次 WebSocket 请求：`modem` 缺省时落到注册表的默认模块上，这样旧的
+/// "纯 AT 命令字符串" 客户端在单模部署下完全不用改。
+#[derive(Deserialize)]
+struct CommandRequest {
+    #[serde(default)]
+    modem: Option<String>,
+    command: String,
+}
+
+/// `{method, params, id}` 形式的命名 RPC 请求：`id` 原样回显到响应里，
+/// 供前端关联请求/响应；不关心响应顺序的客户端可以不传。
+#[derive(Deserialize)]
+struct RpcFrame {
+    #[serde(default)]
+    modem: Option<String>,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+    #[serde(default)]
+    id: serde_json::Value,
+}
+
+/// `{"type":"subscribe","topics":["signal","sms"]}`：客户端用它收窄自己
+/// 想要的 URC 主题。不发这个帧的旧客户端维持原样，照样收到全部 URC。
+#[derive(Deserialize)]
+struct SubscribeFrame {
+    #[serde(rename = "type")]
+    frame_type: String,
+    #[serde(default)]
+    topics: Vec<String>,
+}
 
 /// WebSocket 连接处理器
 pub async fn handle_connection(
-    stream: TcpStream,
+    stream: MaybeTlsStream,
     addr: std::net::SocketAddr,
-    client: Arc<ATClient>,
+    registry: Arc<ModemRegistry>,
     auth_key: String,
+    ban_guard: Arc<BanGuard>,
+    rpc_registry: Arc<RpcRegistry>,
 ) -> Option<()> {
     let ws_stream = accept_async(stream).await.ok()?;
     let (mut ws_tx, mut ws_rx) = ws_stream.split();
-    let mut urc_rx = client.urc_tx.subscribe();
+
+    // 每个模块的 URC 广播各起一个转发任务，打上来源模块 ID 后汇入同一条
+    // 队列，这样下面的主循环只需要 select 一个 receiver。
+    let (urc_tx, mut urc_rx) = mpsc::unbounded_channel::<(String, String)>();
+    for id in registry.ids() {
+        if let Some(client) = registry.get(&id) {
+            let mut sub = client.urc_tx.subscribe();
+            let tx = urc_tx.clone();
+            tokio::spawn(async move {
+                while let Ok(msg) = sub.recv().await {
+                    if tx.send((id.clone(), msg)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+    drop(urc_tx);
+
+    // 同样的扇入方式，转发已经分类好的结构化 `ModemEvent`，供下面按类型
+    // 打成 `{"type":"sms"|"call"|...}`，而不是只有 `raw_data`。
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<(String, ModemEvent)>();
+    for id in registry.ids() {
+        if let Some(client) = registry.get(&id) {
+            let mut sub = client.event_tx.subscribe();
+            let tx = event_tx.clone();
+            tokio::spawn(async move {
+                while let Ok(event) = sub.recv().await {
+                    if tx.send((id.clone(), event)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+    drop(event_tx);
 
     println!("[WebSocket] 新连接: {}", addr);
 
@@ -41,6 +116,7 @@ pub async fn handle_connection(
 
         if !auth_result {
             println!("[WebSocket] 认证失败: {}", addr);
+            ban_guard.record_failure(addr.ip());
             let _ = ws_tx
                 .send(Message::Text(
                     json!({
@@ -53,7 +129,8 @@ pub async fn handle_connection(
             return None;
         }
 
-        // 认证成功
+        // 认证成功：清空该 IP 之前的失败记录，避免偶尔手误被连坐。
+        ban_guard.record_success(addr.ip());
         let _ = ws_tx
             .send(Message::Text(
                 json!({
@@ -66,21 +143,74 @@ pub async fn handle_connection(
         println!("[WebSocket] 认证成功: {}", addr);
     }
 
+    // `None` 表示未订阅过，即不过滤，转发全部 URC（兼容老客户端）；一旦
+    // 收到过一次 subscribe 帧，就只转发主题落在集合里的 URC。
+    let mut subscribed_topics: Option<HashSet<String>> = None;
+
     loop {
         tokio::select! {
-            urc_res = urc_rx.recv() => {
-                if let Ok(msg) = urc_res {
-                    let payload = json!({ "type": "raw_data", "data": msg });
-                    if let Ok(json_str) = serde_json::to_string(&payload) {
-                        if let Err(_) = ws_tx.send(Message::Text(json_str)).await { break; }
+            urc = urc_rx.recv() => {
+                match urc {
+                    Some((modem_id, msg)) => {
+                        let topic = urc_topic(&msg);
+                        let allowed = subscribed_topics
+                            .as_ref()
+                            .map(|topics| topics.contains(topic))
+                            .unwrap_or(true);
+                        if allowed {
+                            let payload = json!({ "type": "raw_data", "modem": modem_id, "topic": topic, "data": msg });
+                            if let Ok(json_str) = serde_json::to_string(&payload) {
+                                if let Err(_) = ws_tx.send(Message::Text(json_str)).await { break; }
+                            }
+                        }
+                    }
+                    None => {}
+                }
+            }
+            event = event_rx.recv() => {
+                if let Some((modem_id, event)) = event {
+                    let (topic, payload) = event_payload(&modem_id, &event);
+                    let allowed = subscribed_topics
+                        .as_ref()
+                        .map(|topics| topics.contains(topic))
+                        .unwrap_or(true);
+                    if allowed {
+                        if let Ok(json_str) = serde_json::to_string(&payload) {
+                            if let Err(_) = ws_tx.send(Message::Text(json_str)).await { break; }
+                        }
                     }
                 }
             }
             msg = ws_rx.next() => {
-                if let Some(Ok(Message::Text(cmd))) = msg {
-                    let res = match client.send_command(cmd).await {
-                        Ok(r) => json!({ "success": true, "data": r, "error": null }),
-                        Err(e) => json!({ "success": false, "data": null, "error": e.to_string() }),
+                if let Some(Ok(Message::Text(raw))) = msg {
+                    if let Ok(frame) = serde_json::from_str::<SubscribeFrame>(&raw) {
+                        if frame.frame_type == "subscribe" {
+                            println!("[WebSocket] {} 订阅主题: {:?}", addr, frame.topics);
+                            let topics: HashSet<String> = frame.topics.into_iter().collect();
+                            let ack = json!({ "type": "subscribed", "topics": topics });
+                            subscribed_topics = Some(topics);
+                            let _ = ws_tx.send(Message::Text(ack.to_string())).await;
+                            continue;
+                        }
+                    }
+
+                    let res = if let Ok(frame) = serde_json::from_str::<RpcFrame>(&raw) {
+                        dispatch_rpc(&registry, &rpc_registry, frame).await
+                    } else {
+                        let (modem_id, command) = parse_request(&raw, &registry.default_id());
+                        match registry.scheduler(&modem_id) {
+                            // 交互命令走最高优先级，排在心跳/调度任务前面派发。
+                            Some(scheduler) => match scheduler.submit_str(Priority::Interactive, command).await {
+                                Ok(r) => json!({ "success": true, "modem": modem_id, "data": r, "error": null }),
+                                Err(e) => json!({ "success": false, "modem": modem_id, "data": null, "error": e.to_string() }),
+                            },
+                            None => json!({
+                                "success": false,
+                                "modem": modem_id,
+                                "data": null,
+                                "error": format!("未知模块: {}", modem_id),
+                            }),
+                        }
                     };
                     let _ = ws_tx.send(Message::Text(serde_json::to_string(&res).unwrap())).await;
                 } else { break; }
@@ -89,4 +219,51 @@ pub async fn handle_connection(
     }
     println!("[WebSocket] 连接断开: {}", addr);
     Some(())
+}
+
+/// 把一个结构化 `ModemEvent` 打成下发给客户端的 JSON，同时给出它归属的
+/// 订阅主题（与 [`urc_topic`] 用的是同一套主题名，好让一次 subscribe 既
+/// 筛原始行也筛类型化事件）。
+fn event_payload(modem_id: &str, event: &ModemEvent) -> (&'static str, Value) {
+    match event {
+        ModemEvent::Sms { index } => ("sms", json!({ "type": "sms", "modem": modem_id, "index": index })),
+        ModemEvent::Call { from } => ("call", json!({ "type": "call", "modem": modem_id, "from": from })),
+        ModemEvent::MemoryFull => ("sms", json!({ "type": "memory_full", "modem": modem_id })),
+        ModemEvent::Signal { rssi } => ("signal", json!({ "type": "signal", "modem": modem_id, "rssi": rssi })),
+    }
+}
+
+/// 解析一条收到的消息：可以是 `{"modem": "sim2", "command": "AT+CSQ"}`
+/// 形式的结构化请求，也可以是一段裸 AT 命令文本（落到默认模块上，兼容
+/// 多模支持之前的客户端）。
+fn parse_request(raw: &str, default_modem: &str) -> (String, String) {
+    match serde_json::from_str::<CommandRequest>(raw) {
+        Ok(req) => (req.modem.unwrap_or_else(|| default_modem.to_string()), req.command),
+        Err(_) => (default_modem.to_string(), raw.to_string()),
+    }
+}
+
+/// 把一条 `RpcFrame` 路由到对应模块的 `RpcContext` 上分发，统一包装成
+/// `{id, success, modem, result, error}` 响应。
+async fn dispatch_rpc(registry: &Arc<ModemRegistry>, rpc_registry: &Arc<RpcRegistry>, frame: RpcFrame) -> Value {
+    let modem_id = frame.modem.unwrap_or_else(|| registry.default_id());
+
+    let (client, scheduler) = match (registry.get(&modem_id), registry.scheduler(&modem_id)) {
+        (Some(client), Some(scheduler)) => (client, scheduler),
+        _ => {
+            return json!({
+                "id": frame.id,
+                "success": false,
+                "modem": modem_id,
+                "result": null,
+                "error": format!("未知模块: {}", modem_id),
+            });
+        }
+    };
+
+    let ctx = RpcContext { client, scheduler };
+    match rpc_registry.dispatch(&frame.method, ctx, frame.params).await {
+        Ok(result) => json!({ "id": frame.id, "success": true, "modem": modem_id, "result": result, "error": null }),
+        Err(e) => json!({ "id": frame.id, "success": false, "modem": modem_id, "result": null, "error": e }),
+    }
 }
\ No newline at end of file
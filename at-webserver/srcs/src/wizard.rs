@@ -0,0 +1,237 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::{self, Config};
+
+/// 交互式地采集配置项，通过 `uci set`/`uci commit` 写回，使其与
+/// `load_config_from_uci` 读取的同一份键值来回可用。
+pub fn run_wizard() -> Result<(), Box<dyn Error>> {
+    println!("{}", "=".repeat(60));
+    println!("at-webserver 配置向导");
+    println!("{}", "=".repeat(60));
+
+    let conn_type = prompt_choice(
+        "连接类型",
+        &["NETWORK", "SERIAL", "TOM_MODEM"],
+        "NETWORK",
+    )?;
+    uci_set("connection_type", &conn_type)?;
+
+    if conn_type == "NETWORK" {
+        let host = prompt_validated(
+            "模块网络地址",
+            "192.168.8.1",
+            |v| !v.trim().is_empty(),
+            "地址不能为空",
+        )?;
+        let port = prompt_validated(
+            "模块网络端口",
+            "20249",
+            |v| v.trim().parse::<u16>().is_ok(),
+            "请输入 1-65535 之间的端口号",
+        )?;
+
+        if !tcp_reachable(&host, &port) {
+            println!("⚠ 无法连接到 {}:{}，请确认配置无误后继续", host, port);
+        }
+
+        uci_set("network_host", &host)?;
+        uci_set("network_port", &port)?;
+    } else {
+        let port = prompt_validated(
+            "串口设备路径",
+            "/dev/ttyUSB0",
+            |v| Path::new(v.trim()).exists(),
+            "该路径不存在，请确认设备已连接",
+        )?;
+        let baudrate = prompt_validated(
+            "波特率",
+            "115200",
+            |v| v.trim().parse::<u32>().is_ok(),
+            "波特率必须是数字",
+        )?;
+
+        uci_set("serial_port", &port)?;
+        uci_set("serial_baudrate", &baudrate)?;
+
+        if conn_type == "TOM_MODEM" {
+            let feature = prompt_choice("TomModem 功能", &["UBUS", "NONE"], "UBUS")?;
+            uci_set("serial_method", "TOM_MODEM")?;
+            uci_set("serial_feature", &feature)?;
+        } else {
+            uci_set("serial_method", "DIRECT")?;
+        }
+    }
+
+    let ws_port = prompt_validated(
+        "WebSocket 监听端口",
+        "8765",
+        |v| v.trim().parse::<u16>().is_ok(),
+        "请输入 1-65535 之间的端口号",
+    )?;
+    uci_set("websocket_port", &ws_port)?;
+
+    let auth_key = prompt_optional("WebSocket 认证密钥（留空表示不校验）")?;
+    uci_set("websocket_auth_key", &auth_key)?;
+
+    let webhook = prompt_optional("企业微信推送 Webhook（留空表示不启用）")?;
+    uci_set("wechat_webhook", &webhook)?;
+
+    uci_commit()?;
+
+    println!("{}", "=".repeat(60));
+    println!("✓ 配置已写入 UCI，可使用 --check 验证结果");
+    println!("{}", "=".repeat(60));
+
+    Ok(())
+}
+
+/// 加载配置并打印将被实际使用的值，不启动任何服务器。
+pub fn run_check() -> Result<(), Box<dyn Error>> {
+    let config = config::load_config_from_uci()?;
+    print_effective_config(&config);
+    Ok(())
+}
+
+fn print_effective_config(config: &Config) {
+    println!("{}", "=".repeat(60));
+    println!("--check: 当前将被使用的配置");
+    println!("{}", "=".repeat(60));
+    let at_config = config.primary_at_config();
+    println!("连接类型: {}", at_config.conn_type);
+    if at_config.conn_type == "NETWORK" {
+        println!(
+            "  网络地址: {}:{} (超时 {}秒)",
+            at_config.network.host, at_config.network.port, at_config.network.timeout
+        );
+    } else {
+        println!(
+            "  串口: {} @ {} bps ({} / {})",
+            at_config.serial.port, at_config.serial.baudrate, at_config.serial.method, at_config.serial.feature
+        );
+    }
+    if config.at_configs.len() > 1 {
+        let mut extra: Vec<&String> = config
+            .at_configs
+            .keys()
+            .filter(|id| id.as_str() != "default")
+            .collect();
+        extra.sort();
+        println!(
+            "附加模块: {}",
+            extra
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    println!(
+        "WebSocket: [{}]:{} / {}:{}",
+        config.websocket_config.ipv6.host,
+        config.websocket_config.ipv6.port,
+        config.websocket_config.ipv4.host,
+        config.websocket_config.ipv4.port
+    );
+    println!(
+        "认证密钥: {}",
+        if config.websocket_config.auth_key.is_empty() {
+            "未设置"
+        } else {
+            "已设置"
+        }
+    );
+    println!("推送后端:");
+    if config.notification_config.backends.is_empty() {
+        println!("  (未配置)");
+    }
+    for backend in &config.notification_config.backends {
+        println!(
+            "  - {}: {}",
+            backend.kind,
+            if backend.enabled { "已启用" } else { "未启用" }
+        );
+    }
+    println!("{}", "=".repeat(60));
+}
+
+fn prompt_choice(label: &str, options: &[&str], default: &str) -> Result<String, Box<dyn Error>> {
+    loop {
+        print!("{} [{}] (默认 {}): ", label, options.join("/"), default);
+        io::stdout().flush()?;
+        let input = read_line()?;
+        let value = if input.trim().is_empty() {
+            default.to_string()
+        } else {
+            input.trim().to_uppercase()
+        };
+        if options.contains(&value.as_str()) {
+            return Ok(value);
+        }
+        println!("请输入以下选项之一: {}", options.join("/"));
+    }
+}
+
+fn prompt_validated(
+    label: &str,
+    default: &str,
+    is_valid: impl Fn(&str) -> bool,
+    error_msg: &str,
+) -> Result<String, Box<dyn Error>> {
+    loop {
+        print!("{} (默认 {}): ", label, default);
+        io::stdout().flush()?;
+        let input = read_line()?;
+        let value = if input.trim().is_empty() {
+            default.to_string()
+        } else {
+            input.trim().to_string()
+        };
+        if is_valid(&value) {
+            return Ok(value);
+        }
+        println!("✗ {}", error_msg);
+    }
+}
+
+fn prompt_optional(label: &str) -> Result<String, Box<dyn Error>> {
+    print!("{}: ", label);
+    io::stdout().flush()?;
+    Ok(read_line()?.trim().to_string())
+}
+
+fn read_line() -> Result<String, Box<dyn Error>> {
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input)
+}
+
+fn tcp_reachable(host: &str, port: &str) -> bool {
+    let addr = format!("{}:{}", host, port);
+    match addr.parse() {
+        Ok(socket_addr) => TcpStream::connect_timeout(&socket_addr, Duration::from_secs(2)).is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn uci_set(key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    let status = Command::new("uci")
+        .args(&["set", &format!("at-webserver.config.{}={}", key, value)])
+        .status()?;
+    if !status.success() {
+        return Err(format!("uci set {} 失败", key).into());
+    }
+    Ok(())
+}
+
+fn uci_commit() -> Result<(), Box<dyn Error>> {
+    let status = Command::new("uci").args(&["commit", "at-webserver"]).status()?;
+    if !status.success() {
+        return Err("uci commit 失败".into());
+    }
+    Ok(())
+}